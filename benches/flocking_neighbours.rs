@@ -0,0 +1,105 @@
+//! Compares the old brute force neighbour search with the spatial index used by
+//! `sheep::find_flocking_neighbours`.
+//!
+//! Run with `cargo bench --bench flocking_neighbours`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use sylt::imports::*;
+use sylt::imports::sheep::{Flocking, NeighbourPair};
+
+/// Sheep are spread with roughly the density of the clusters in the shipped levels.
+const AREA_PER_SHEEP: f32 = 40.0 * 40.0;
+
+fn generate_flock(num_sheep: usize) -> Vec<SpatialEntry> {
+    let mut rng = StdRng::seed_from_u64(num_sheep as u64);
+    let half_extent = (num_sheep as f32 * AREA_PER_SHEEP).sqrt() / 2.0;
+    (0..num_sheep).map(|i| SpatialEntry {
+        entity: Entity::from_raw(i as u32),
+        position: Vec3::new(
+            rng.gen_range(-half_extent..half_extent),
+            rng.gen_range(-half_extent..half_extent),
+            0.0,
+        ),
+        velocity: Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0.0) * 100.0,
+    }).collect()
+}
+
+/// The neighbour search as it was before the spatial index: every sheep against every other.
+fn brute_force(flock: &[SpatialEntry], config: &FlockingConfiguration, results: &mut [Flocking]) {
+    for (current, flocking) in flock.iter().zip(results.iter_mut()) {
+        flocking.alignment_values.clear();
+        flocking.cohesion_positions.clear();
+        flocking.separation_positions.clear();
+        for other in flock.iter() {
+            if current.entity == other.entity { continue; }
+            let distance = current.position.distance(other.position);
+            if distance < config.alignment_distance && config.alignment_enabled {
+                flocking.alignment_values.push(NeighbourPair {
+                    position: other.position,
+                    velocity: other.velocity,
+                });
+            }
+            if distance < config.cohesion_distance && config.cohesion_enabled {
+                flocking.cohesion_positions.push(other.position);
+            }
+            if distance < config.separation_distance && config.separation_enabled {
+                flocking.separation_positions.push(other.position);
+            }
+        }
+    }
+}
+
+fn spatial_index(
+    flock: &[SpatialEntry],
+    config: &FlockingConfiguration,
+    index: &mut SpatialIndex,
+    scratch: &mut Vec<usize>,
+    results: &mut [Flocking],
+) {
+    index.rebuild(config.max_neighbour_distance(), flock.iter().cloned());
+    for (current, flocking) in flock.iter().zip(results.iter_mut()) {
+        flocking.collect_neighbours(current.entity, current.position, index, config, scratch);
+    }
+}
+
+fn assert_same_neighbours(expected: &[Flocking], actual: &[Flocking]) {
+    for (expected, actual) in expected.iter().zip(actual.iter()) {
+        assert_eq!(expected.cohesion_positions, actual.cohesion_positions);
+        assert_eq!(expected.separation_positions, actual.separation_positions);
+        assert_eq!(expected.alignment_values.len(), actual.alignment_values.len());
+        for (expected, actual) in expected.alignment_values.iter().zip(actual.alignment_values.iter()) {
+            assert_eq!(expected.position, actual.position);
+            assert_eq!(expected.velocity, actual.velocity);
+        }
+    }
+}
+
+fn flocking_neighbours(c: &mut Criterion) {
+    let config = Configuration::new().flocking;
+    let mut group = c.benchmark_group("flocking_neighbours");
+    group.sample_size(10);
+    for num_sheep in [100, 1000, 5000] {
+        let flock = generate_flock(num_sheep);
+        let mut brute_force_results: Vec<Flocking> = (0..num_sheep).map(|_| Flocking::default()).collect();
+        let mut index_results: Vec<Flocking> = (0..num_sheep).map(|_| Flocking::default()).collect();
+        let mut index = SpatialIndex::default();
+        let mut scratch = Vec::new();
+
+        brute_force(&flock, &config, &mut brute_force_results);
+        spatial_index(&flock, &config, &mut index, &mut scratch, &mut index_results);
+        assert_same_neighbours(&brute_force_results, &index_results);
+
+        group.bench_with_input(BenchmarkId::new("brute_force", num_sheep), &flock, |b, flock| {
+            b.iter(|| brute_force(black_box(flock), &config, &mut brute_force_results));
+        });
+        group.bench_with_input(BenchmarkId::new("spatial_index", num_sheep), &flock, |b, flock| {
+            b.iter(|| spatial_index(black_box(flock), &config, &mut index, &mut scratch, &mut index_results));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, flocking_neighbours);
+criterion_main!(benches);
@@ -10,30 +10,56 @@ impl Plugin for AnimationPlugin {
             .add_system_set(
                 SystemSet::new()
                     .label("animation")
-                    .after("motion")
-                    // .with_run_criteria(FixedTimestep::step(TIME_STEP as f64))
                     .with_system(animate_sprite)
             );
     }
 }
 
 
-#[derive(Component, Default)]
+/// Where an entity is in the state machine of its animation sheet.
+#[derive(Component)]
 pub struct AnimationStates {
-    current: AnimationType,
-    next: AnimationType,
+    current: String,
+    /// The state to go back to once the current one-shot animation has played.
+    return_to: Option<String>,
     next_flip: bool,
+    time_in_state: f32,
+}
+
+impl AnimationStates {
+    fn new(initial: &str) -> Self {
+        AnimationStates {
+            current: String::from(initial),
+            return_to: None,
+            next_flip: false,
+            time_in_state: 0.0,
+        }
+    }
+
+    pub fn current(&self) -> &str {
+        &self.current
+    }
+
+    fn enter(&mut self, state: &str, sheet: &AnimationSheet, sprite: &mut TextureAtlasSprite, timer: &mut AnimationTimer) {
+        let animation = sheet.get_anim(state);
+        if !animation.one_shot {
+            self.return_to = None;
+        } else if !sheet.get_anim(&self.current).one_shot {
+            self.return_to = Some(self.current.clone());
+        }
+        let animation_interval = if animation.animation_interval == 0.0 { 0.3 } else { animation.animation_interval };
+        timer.set_elapsed(Duration::from_secs_f32(0.0));
+        timer.set_duration(Duration::try_from_secs_f32(animation_interval).unwrap_or(Duration::from_secs(1)));
+        sprite.index = animation.first_index;
+        self.current = String::from(state);
+        self.time_in_state = 0.0;
+    }
 }
 
 #[derive(Component, Default)]
 pub struct RandomInitAnimation;
 
-#[derive(Copy, Clone, PartialEq, Default)]
-pub enum AnimationType {
-    #[default]
-    Idle,
-    Running,
-}
+pub const IDLE_ANIMATION: &str = "idle";
 
 
 #[derive(Bundle)]
@@ -45,16 +71,13 @@ pub struct AnimationBundle {
 
 impl AnimationBundle {
     pub fn from(anim_sheet: &AnimationSheet, position: Vec3) -> Self {
-        let default_animation = match &anim_sheet.animation_class {
-            AnimationClass::Simple { simple } => { simple }
-            AnimationClass::Actor { idle, .. } => { idle }
-        };
+        let default_animation = anim_sheet.get_anim(&anim_sheet.initial_animation);
         Self {
             sprite_sheet: SpriteSheetBundle {
                 texture_atlas: anim_sheet.sprite_sheet_handle.clone().expect(
                     &format!("all sprite sheets should be loaded for the game to run, missing {}", anim_sheet.sprite_sheet)),
                 sprite: TextureAtlasSprite {
-                    index: anim_sheet.clamp_index(default_animation.first_index),
+                    index: default_animation.first_index,
                     custom_size: Some(anim_sheet.texture_size * GLOBAL_TEXTURE_SCALE),
                     ..default()
                 },
@@ -62,95 +85,130 @@ impl AnimationBundle {
                 ..default()
             },
             animation_timer: AnimationTimer(Timer::from_seconds(default_animation.animation_interval, TimerMode::Repeating)),
-            states: AnimationStates::default(),
+            states: AnimationStates::new(&anim_sheet.initial_animation),
         }
     }
 }
+
 impl AnimationSheet {
-    fn get_anim(self: &Self, anim: AnimationType) -> &SingleAnimation {
-        match &self.animation_class {
-            AnimationClass::Simple { simple } => { &simple }
-            AnimationClass::Actor { idle, running, .. } => {
-                match anim {
-                    AnimationType::Idle => &idle,
-                    AnimationType::Running => &running,
-                }
-            }
-        }
+    fn get_anim(self: &Self, state: &str) -> &SingleAnimation {
+        self.animations.get(state)
+            .expect("animation sheets are validated to only refer to their own animations")
     }
-    fn get_num_textures(self: &Self) -> usize {
-        self.atlas_tile_columns * self.atlas_tile_rows
+
+    fn find_transition(&self, states: &AnimationStates, context: &AnimationContext) -> Option<&AnimationTransition> {
+        let one_shot = self.get_anim(&states.current).one_shot;
+        self.transitions.iter().find(|transition| {
+            transition.to != states.current
+                && if transition.from.is_empty() { !one_shot } else { transition.from.contains(&states.current) }
+                && transition.when.holds(context)
+        })
     }
-    fn clamp_index(self: &Self, index: usize) -> usize {
-        std::cmp::min(self.get_num_textures() - 1, index)
+}
+
+/// What the conditions of the state machine are checked against.
+struct AnimationContext<'a> {
+    speed_fraction: Option<f32>,
+    grazing: Option<&'a sheep::Grazing>,
+    runner: Option<&'a sheep::Runner>,
+    barked: bool,
+    time_in_state: f32,
+}
+
+impl AnimationCondition {
+    fn holds(&self, context: &AnimationContext) -> bool {
+        match self {
+            AnimationCondition::Always => true,
+            AnimationCondition::SpeedAbove(fraction) => context.speed_fraction.map_or(false, |speed| speed > *fraction),
+            AnimationCondition::SpeedBelow(fraction) => context.speed_fraction.map_or(true, |speed| speed < *fraction),
+            AnimationCondition::Grazing => context.grazing.map_or(false, |grazing| grazing.current_direction.is_none()),
+            AnimationCondition::Scared(magnitude) => context.runner.map_or(false, |runner| runner.magnitude >= *magnitude),
+            AnimationCondition::Barked => context.barked,
+            AnimationCondition::InStateFor(seconds) => context.time_in_state >= *seconds,
+            AnimationCondition::All(conditions) => conditions.iter().all(|condition| condition.holds(context)),
+            AnimationCondition::Not(condition) => !condition.holds(context),
+        }
     }
 }
+
 #[derive(Component, Deref, DerefMut)]
 pub struct AnimationTimer(pub Timer);
 
 pub fn animate_sprite(
     mut query: Query<(
+        Entity,
         &mut TextureAtlasSprite,
         &mut AnimationTimer,
         &mut AnimationStates,
         &ConfigurationSetId,
         Option<&Velocity>,
+        Option<&sheep::Grazing>,
+        Option<&sheep::Runner>,
     )>,
+    mut bark_events: EventReader<player::BarkEvent>,
     config: Res<Configuration>,
     time: Res<Time>,
 ) {
+    let barkers: Vec<Entity> = bark_events.iter().map(|event| event.barker).collect();
     for (
+        entity,
         mut sprite,
         mut timer,
         mut states,
         config_id,
-        velocity
+        velocity,
+        grazing,
+        runner,
     ) in query.iter_mut() {
-        let config_set = config.animation.get_set(config_id);
-        if let Some(velocity) = velocity {
-            match &config_set.animation_class {
-                AnimationClass::Actor { run_threshold_fraction, flip_threshold_fraction, .. } => {
-                    let max_speed = config.get_set(config_id).max_speed;
-                    let speed_fraction = velocity.linvel.length() / max_speed;
-                    if speed_fraction > *run_threshold_fraction {
-                        states.next = AnimationType::Running;
-                    } else {
-                        states.next = AnimationType::Idle;
-                    }
-                    let x_speed_fraction = velocity.linvel.x / max_speed;
-                    if x_speed_fraction > *flip_threshold_fraction {
-                        states.next_flip = true;
-                    } else if x_speed_fraction < -*flip_threshold_fraction {
-                        states.next_flip = false;
-                    }
-                }
-                _ => {}
+        let sheet = config.get_sheet(config_id);
+        let max_speed = config.get_set(config_id).max_speed;
+        // Something that can not move has no speed to scale by, treat it as standing still
+        let fraction_of_max = |speed: f32| if max_speed > 0.0 { speed / max_speed } else { 0.0 };
+        let speed_fraction = velocity.map(|velocity| fraction_of_max(velocity.linvel.length()));
+        let mut snappy_flip = false;
+        if let (Some(velocity), Some(movement)) = (velocity, &sheet.movement) {
+            let x_speed_fraction = fraction_of_max(velocity.linvel.x);
+            if x_speed_fraction > movement.flip_threshold_fraction {
+                states.next_flip = true;
+            } else if x_speed_fraction < -movement.flip_threshold_fraction {
+                states.next_flip = false;
             }
+            snappy_flip = movement.snappy_flip;
         }
-        let set = config.animation.get_set(config_id);
-        let next_anim_config = set.get_anim(states.next);
-        if timer.just_finished() || config_set.snappy_animations {
-            if states.current != states.next {
-                let animation = config_set.get_anim(states.next);
-                let animation_interval = if animation.animation_interval == 0.0 { 0.3 } else { animation.animation_interval };
-                timer.set_elapsed(Duration::from_secs_f32(0.0));
-                timer.set_duration(Duration::try_from_secs_f32(animation_interval).unwrap_or(Duration::from_secs(1)));
-                sprite.index = set.clamp_index(next_anim_config.first_index);
-                states.current = states.next;
-            }
-            if sprite.flip_x != states.next_flip {
-                sprite.flip_x = states.next_flip
+        states.time_in_state += time.delta_seconds();
+
+        let context = AnimationContext {
+            speed_fraction,
+            grazing,
+            runner,
+            barked: barkers.contains(&entity),
+            time_in_state: states.time_in_state,
+        };
+        if let Some(transition) = sheet.find_transition(&states, &context) {
+            if transition.snappy || timer.just_finished() {
+                states.enter(&transition.to, sheet, &mut sprite, &mut timer);
             }
         }
-        let current_anim_config = set.get_anim(states.current);
+        if (timer.just_finished() || snappy_flip) && sprite.flip_x != states.next_flip {
+            sprite.flip_x = states.next_flip;
+        }
+
+        let current_anim_config = sheet.get_anim(&states.current);
+        if let (Some(playback), Some(speed_fraction)) = (&current_anim_config.playback, speed_fraction) {
+            let duration = Duration::try_from_secs_f32(1.0 / playback.frame_rate(speed_fraction)).unwrap_or(timer.duration());
+            timer.set_duration(duration);
+        }
         timer.tick(time.delta());
         if timer.just_finished() {
-            let sprite_index = if sprite.index == current_anim_config.last_index {
-                current_anim_config.first_index
+            let at_last_frame = sprite.index >= current_anim_config.last_index || sprite.index < current_anim_config.first_index;
+            if at_last_frame && current_anim_config.one_shot {
+                let return_to = states.return_to.take().unwrap_or_else(|| sheet.initial_animation.clone());
+                states.enter(&return_to, sheet, &mut sprite, &mut timer);
+            } else if at_last_frame {
+                sprite.index = current_anim_config.first_index;
             } else {
-                sprite.index + 1
-            };
-            sprite.index = set.clamp_index(sprite_index)
+                sprite.index += 1;
+            }
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,143 @@
+use crate::imports::*;
+use crate::levels::LevelSelection;
+
+pub const USAGE: &str = "\
+Usage: sylt [options]
+
+Options:
+    --editor                 Open the level editor
+    --level <file or index>  Start at a level, either a .yol file relative to the assets folder
+                             or an index into levels/index.yoli
+    --seed <n>               Seed for the simulation
+    --config <preset>        Name of a configuration preset in assets/configuration to use instead
+                             of the default, e.g. default
+    --headless               Run the level without a window and print the outcome
+    --ticks <n>              Number of simulation ticks to run in headless mode (default 3600),
+                             or per level when verifying (default 10800)
+    --autopilot              Let the autopilot herd with every dog, press H to toggle it in game
+    --verify-levels          Check that the autopilot can solve every level in levels/index.yoli,
+                             with seed 1 unless --seed is given
+    --window-size <w>x<h>    Size of the window, e.g. 1600x1000
+    --debug-lines            Start with the influence debug lines enabled
+    --help                   Print this message";
+
+pub const DEFAULT_HEADLESS_TICKS: u32 = 3600;
+pub const DEFAULT_VERIFY_TICKS: u32 = 10800;
+/// Verifying uses a fixed seed by default so a level that passes once keeps passing.
+pub const DEFAULT_VERIFY_SEED: u64 = 1;
+
+/// The options the game can be launched with.
+#[derive(Default)]
+pub struct CliArgs {
+    pub editor: bool,
+    pub level: Option<LevelSelection>,
+    pub seed: Option<u64>,
+    pub config: Option<String>,
+    pub headless: bool,
+    pub autopilot: bool,
+    pub verify_levels: bool,
+    pub ticks: Option<u32>,
+    pub window_size: Option<Vec2>,
+    pub debug_lines: bool,
+    pub help: bool,
+}
+
+impl CliArgs {
+    pub fn from_env() -> Result<Self, String> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse(mut args: impl Iterator<Item=String>) -> Result<Self, String> {
+        let mut cli_args = CliArgs::default();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+            match arg.as_str() {
+                "--editor" => cli_args.editor = true,
+                "--headless" => cli_args.headless = true,
+                "--autopilot" => cli_args.autopilot = true,
+                "--verify-levels" => cli_args.verify_levels = true,
+                "--debug-lines" => cli_args.debug_lines = true,
+                "--help" | "-h" => cli_args.help = true,
+                "--level" => cli_args.level = Some(LevelSelection::parse(&value("--level")?)),
+                "--seed" => {
+                    let seed = value("--seed")?;
+                    cli_args.seed = Some(seed.parse().map_err(|_| format!("invalid seed {}", seed))?);
+                }
+                "--config" => cli_args.config = Some(presets::parse_preset_name(&value("--config")?)?),
+                "--ticks" => {
+                    let ticks = value("--ticks")?;
+                    cli_args.ticks = Some(ticks.parse().map_err(|_| format!("invalid number of ticks {}", ticks))?);
+                }
+                "--window-size" => {
+                    let size = value("--window-size")?;
+                    cli_args.window_size = Some(parse_window_size(&size)
+                        .ok_or_else(|| format!("invalid window size {}, expected e.g. 1600x1000", size))?);
+                }
+                _ => return Err(format!("unknown argument {}", arg)),
+            }
+        }
+        if cli_args.headless && cli_args.editor {
+            return Err(String::from("--headless and --editor can not be combined"));
+        }
+        if cli_args.level.is_some() && cli_args.editor {
+            return Err(String::from("--level and --editor can not be combined, open levels from the editor instead"));
+        }
+        if cli_args.verify_levels && (cli_args.editor || cli_args.headless || cli_args.level.is_some()) {
+            return Err(String::from("--verify-levels can not be combined with --editor, --headless or --level"));
+        }
+        if cli_args.ticks.is_some() && !cli_args.headless && !cli_args.verify_levels {
+            return Err(String::from("--ticks only applies to --headless and --verify-levels"));
+        }
+        Ok(cli_args)
+    }
+}
+
+fn parse_window_size(value: &str) -> Option<Vec2> {
+    let (width, height) = value.split_once('x')?;
+    let size = Vec2::new(width.trim().parse().ok()?, height.trim().parse().ok()?);
+    (size.x > 0.0 && size.y > 0.0).then_some(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        CliArgs::parse(args.iter().map(|arg| String::from(*arg)))
+    }
+
+    #[test]
+    fn parses_valid_arguments() {
+        let args = parse(&["--headless", "--level", "2", "--seed", "7", "--ticks", "60", "--config", "default.config.json"]).unwrap();
+        assert!(args.headless);
+        assert_eq!(args.seed, Some(7));
+        assert_eq!(args.ticks, Some(60));
+        assert_eq!(args.config.as_deref(), Some("default"));
+        assert!(matches!(args.level, Some(LevelSelection::Index(2))));
+        assert_eq!(parse(&["--window-size", "1600x1000"]).unwrap().window_size, Some(Vec2::new(1600.0, 1000.0)));
+    }
+
+    #[test]
+    fn rejects_conflicting_modes() {
+        assert!(parse(&["--headless", "--editor"]).is_err());
+        assert!(parse(&["--level", "1", "--editor"]).is_err());
+        assert!(parse(&["--verify-levels", "--editor"]).is_err());
+        assert!(parse(&["--verify-levels", "--headless"]).is_err());
+        assert!(parse(&["--verify-levels", "--level", "1"]).is_err());
+        assert!(parse(&["--ticks", "10"]).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert!(parse(&["--seed"]).is_err());
+        assert!(parse(&["--seed", "sheep"]).is_err());
+        assert!(parse(&["--headless", "--ticks", "-1"]).is_err());
+        assert!(parse(&["--window-size", "1600"]).is_err());
+        assert!(parse(&["--window-size", "0x1000"]).is_err());
+        assert!(parse(&["--config", "assets/configuration/default.config.json"]).is_err());
+        assert!(parse(&["--config", "..\\default"]).is_err());
+        assert!(parse(&["--config", ".."]).is_err());
+        assert!(parse(&["--config", ""]).is_err());
+        assert!(parse(&["--sheep"]).is_err());
+    }
+}
@@ -1,19 +1,46 @@
+use bevy::asset::FileAssetIo;
+use bevy::utils::HashMap;
 use bevy_yoleck::{YoleckLevelIndex, YoleckRawLevel};
 use crate::imports::*;
 
 pub const GLOBAL_TEXTURE_SCALE: f32 = 2.0;
 
 impl Configuration {
+    /// The built-in values, overridden by the default preset if there is one.
     pub fn new() -> Self {
+        Self::from_preset(presets::DEFAULT_PRESET)
+    }
+
+    /// The built-in values, overridden by the named preset if there is one. A broken preset is
+    /// logged and skipped so a typo in a tuning file does not stop the game from starting.
+    pub fn from_preset(name: &str) -> Self {
+        let mut config = Self::built_in();
+        match presets::read_preset(name) {
+            Ok(Some(loaded)) => config.apply(loaded),
+            Ok(None) => {}
+            Err(error) => error!("Could not apply configuration preset \"{}\": {}", name, error),
+        }
+        config
+    }
+
+    /// The built-in tuning values, along with the configuration sets and animation sheets.
+    pub fn built_in() -> Self {
+        let animation = AnimationConfiguration::load();
+        let sets = load_configuration_sets(&animation);
+        Self {
+            animation,
+            sets,
+            ..Self::built_in_tuning()
+        }
+    }
+
+    /// The built-in tuning values alone. Fills in whatever a preset leaves out, without reading
+    /// the sets and sheets from disk again for every preset that is parsed.
+    fn built_in_tuning() -> Self {
         Self {
             zoom: 1.0,
-            animation: AnimationConfiguration::new(),
-            player: ConfigurationSet {
-                max_speed: 300.0,
-            },
-            sheep: ConfigurationSet {
-                max_speed: 100.0,
-            },
+            animation: AnimationConfiguration::default(),
+            sets: HashMap::default(),
             flocking: FlockingConfiguration {
                 alignment_enabled: true,
                 alignment_distance: 60.0,
@@ -26,24 +53,142 @@ impl Configuration {
                 separation_enabled: true,
                 separation_distance: 30.0,
                 separation_scale: 30.0,
+                avoidance_enabled: true,
+                avoidance_distance: 60.0,
+                avoidance_scale: 1.0,
             },
             runner: RunnerConfiguration {
                 scale: 10.0,
                 speed_fraction: 1.4,
                 scare_distance: 160.0,
+                calm_speed_fraction: 0.4,
+                calm_scare_fraction: 0.5,
+            },
+            stamina: StaminaConfiguration {
+                enabled: true,
+                sprint_speed: 1.5,
+                drain_rate: 0.3,
+                regen_rate: 0.15,
+                min_to_sprint: 0.25,
+            },
+            autopilot: AutopilotConfiguration {
+                toggle: vec![KeyCode::H],
+                collect_distance: 60.0,
+                interaction_distance: 30.0,
+                slow_distance: 90.0,
+                slow_speed: 0.5,
+                arrival_distance: 60.0,
+            },
+            hunting: HuntingConfiguration {
+                scare_distance: 200.0,
+                isolation_distance: 150.0,
+                stalk_speed_fraction: 0.4,
+                attack_distance: 100.0,
+                catch_distance: 28.0,
+                dog_flee_distance: 150.0,
+                flee_time: 4.0,
+            },
+            bark: BarkConfiguration {
+                cooldown: 2.0,
+                radius: 320.0,
+                magnitude: 1.0,
+            },
+            temperament: TemperamentConfiguration {
+                enabled: true,
+                boldness: TraitDistribution { mean: 1.0, std_dev: 0.2, min: 0.5, max: 1.5 },
+                sociability: TraitDistribution { mean: 1.0, std_dev: 0.2, min: 0.5, max: 1.5 },
+                speed: TraitDistribution { mean: 1.0, std_dev: 0.1, min: 0.8, max: 1.2 },
+                wanderlust: TraitDistribution { mean: 1.0, std_dev: 0.3, min: 0.2, max: 2.0 },
+            },
+            players: vec![
+                PlayerBindings {
+                    up: vec![KeyCode::W, KeyCode::Up],
+                    down: vec![KeyCode::S, KeyCode::Down],
+                    left: vec![KeyCode::A, KeyCode::Left],
+                    right: vec![KeyCode::D, KeyCode::Right],
+                    bark: vec![KeyCode::Space],
+                    sprint: vec![KeyCode::LShift, KeyCode::RShift],
+                    gamepad: Some(0),
+                    scheme: ControlScheme::KeysAndGamepad,
+                    tint: Color::WHITE,
+                },
+                PlayerBindings {
+                    up: vec![KeyCode::I],
+                    down: vec![KeyCode::K],
+                    left: vec![KeyCode::J],
+                    right: vec![KeyCode::L],
+                    bark: vec![KeyCode::O],
+                    sprint: vec![KeyCode::U],
+                    gamepad: Some(1),
+                    scheme: ControlScheme::KeysAndGamepad,
+                    tint: Color::rgb(0.7, 0.8, 1.0),
+                },
+            ],
+            mouse: MouseConfiguration {
+                arrival_distance: 120.0,
+                stop_distance: 8.0,
+            },
+            gamepad: GamepadConfiguration {
+                dead_zone: 0.15,
+                buttons: GamepadButtonBindings::default(),
+            },
+            panic: PanicConfiguration {
+                enabled: true,
+                spread_radius: 50.0,
+                spread_rate: 3.0,
+                decay_rate: 0.5,
+            },
+            grass: GrassConfiguration {
+                enabled: true,
+                eat_radius: 20.0,
+                eat_rate: 0.2,
+                regrow_rate: 0.01,
+                seek_distance: 150.0,
+                seek_bias: 0.7,
+                grazed_below: 0.6,
+                bare_below: 0.2,
+            },
+            needs: NeedsConfiguration {
+                enabled: true,
+                hunger_rate: 0.01,
+                food_nourishment: 0.2,
+                grass_nourishment: 1.0,
+                hunger_resistance: 0.8,
+                energy_drain: 0.08,
+                energy_recovery: 0.05,
+                rest_speed_fraction: 0.2,
+                exhausted_below: 0.3,
+                exhausted_speed: 0.4,
+            },
+            wind: WindConfiguration {
+                enabled: true,
+                direction: 20.0,
+                speed: 120.0,
+                base_strength: 0.2,
+                gust_strength: 0.8,
+                gust_size: 300.0,
+                sway_frequency: 0.6,
+                sway_threshold: 0.45,
+                influence_enabled: false,
+                influence_threshold: 0.7,
+                influence_scale: 0.3,
+            },
+            food: FoodConfiguration {
+                enabled: true,
+                attraction_distance: 400.0,
+                satisfied_distance: 40.0,
+                scale: 2.0,
             },
             grazing_scale: 1.0,
             inertia_scale: 10.0,
             debug_lines: DebugLineConfiguration {
                 enable: false,
-                // red: DebugLineType::None,
-                // green: DebugLineType::None,
-                // blue: DebugLineType::None,
-                // gray: DebugLineType::None,
-                red: DebugLineType::AlignmentInfluence,
-                green: DebugLineType::CohesionInfluence,
-                blue: DebugLineType::InertiaInfluence,
-                gray: DebugLineType::TotalInfluence,
+                influences: vec![
+                    String::from("alignment"),
+                    String::from("cohesion"),
+                    String::from("inertia"),
+                    String::from("total"),
+                ],
             },
             global_assets: GlobalAssets {
                 font_path: String::from("fonts/eight-bit-dragon-font/EightBitDragon-anqx.ttf"),
@@ -53,167 +198,142 @@ impl Configuration {
             },
         }
     }
+    /// Every set an entity can refer to is checked by [`load_configuration_sets`], so an unknown
+    /// id here is a bug rather than bad data.
     pub fn get_set<'a>(self: &'a Self, id: &ConfigurationSetId) -> &'a ConfigurationSet {
-        match id {
-            ConfigurationSetId::Player => {
-                &self.player
-            }
-            ConfigurationSetId::Sheep => {
-                &self.sheep
-            }
-            _ => {
-                &self.sheep
-            }
+        self.sets.get(&id.0).unwrap_or_else(|| panic!("{}", self.unknown_set_error(&id.0)))
+    }
+
+    /// Replaces all tuning values with `loaded`, keeping what is only known at runtime or lives
+    /// in other files: the configuration sets, animation sheets, loaded assets and whether debug
+    /// lines are drawn.
+    pub fn apply(&mut self, mut loaded: Configuration) {
+        loaded.debug_lines.enable = self.debug_lines.enable;
+        loaded.sets = std::mem::take(&mut self.sets);
+        loaded.animation = std::mem::take(&mut self.animation);
+        loaded.global_assets = std::mem::take(&mut self.global_assets);
+        *self = loaded;
+    }
+
+    /// The set a level entity asks for, or `default` when it does not name one. Levels are not
+    /// checked when the sets load, so a name that is not in the sets is an error.
+    pub fn set_id_or(&self, name: Option<&str>, default: &str) -> Result<ConfigurationSetId, String> {
+        match name {
+            Some(name) if self.sets.contains_key(name) => Ok(ConfigurationSetId::new(name)),
+            Some(name) => Err(self.unknown_set_error(name)),
+            None => Ok(ConfigurationSetId::new(default)),
         }
     }
+
+    fn unknown_set_error(&self, name: &str) -> String {
+        let mut known: Vec<&String> = self.sets.keys().collect();
+        known.sort();
+        format!("unknown configuration set \"{}\", the sets in {} are {:?}", name, CONFIGURATION_SETS_PATH, known)
+    }
+
+    /// The animation sheet of the set `id`.
+    pub fn get_sheet(&self, id: &ConfigurationSetId) -> &AnimationSheet {
+        self.animation.get_sheet(&self.get_set(id).animation_sheet)
+    }
+}
+
+/// Gives each nested configuration a `built_in` constructor with the values of
+/// [`Configuration::built_in_tuning`], so presets can leave out single fields of it too.
+macro_rules! built_in_defaults {
+    ($($config:ident => $field:ident),* $(,)?) => {
+        $(
+            impl $config {
+                fn built_in() -> Self {
+                    Configuration::built_in_tuning().$field
+                }
+            }
+        )*
+    };
 }
 
-#[derive(Reflect, Default, Resource, InspectorOptions)]
+built_in_defaults! {
+    FlockingConfiguration => flocking,
+    RunnerConfiguration => runner,
+    StaminaConfiguration => stamina,
+    AutopilotConfiguration => autopilot,
+    HuntingConfiguration => hunting,
+    BarkConfiguration => bark,
+    MouseConfiguration => mouse,
+    GamepadConfiguration => gamepad,
+    PanicConfiguration => panic,
+    GrassConfiguration => grass,
+    NeedsConfiguration => needs,
+    WindConfiguration => wind,
+    TemperamentConfiguration => temperament,
+    FoodConfiguration => food,
+    DebugLineConfiguration => debug_lines,
+}
+
+#[derive(Reflect, Default, Resource, InspectorOptions, serde::Serialize, serde::Deserialize)]
 #[reflect(Resource, InspectorOptions)]
+#[serde(default = "Configuration::built_in_tuning")]
 pub struct Configuration {
     pub zoom: f32,
+    /// Lives in its own file, see [`AnimationConfiguration::load`].
+    #[serde(skip)]
     pub animation: AnimationConfiguration,
-    pub player: ConfigurationSet,
-    pub sheep: ConfigurationSet,
+    /// Stats of every kind of entity, keyed by the name in its `ConfigurationSetId`.
+    /// These live in their own file, see [`load_configuration_sets`].
+    #[serde(skip)]
+    pub sets: HashMap<String, ConfigurationSet>,
     pub flocking: FlockingConfiguration,
     pub runner: RunnerConfiguration,
+    pub stamina: StaminaConfiguration,
+    pub autopilot: AutopilotConfiguration,
+    pub hunting: HuntingConfiguration,
+    pub bark: BarkConfiguration,
+    /// Controls for each local player, indexed by the slot of the player entity.
+    pub players: Vec<PlayerBindings>,
+    pub gamepad: GamepadConfiguration,
+    pub mouse: MouseConfiguration,
+    pub panic: PanicConfiguration,
+    pub grass: GrassConfiguration,
+    pub needs: NeedsConfiguration,
+    pub wind: WindConfiguration,
+    pub temperament: TemperamentConfiguration,
+    pub food: FoodConfiguration,
     pub grazing_scale: f32,
     pub inertia_scale: f32,
     pub debug_lines: DebugLineConfiguration,
+    #[serde(skip)]
     pub global_assets: GlobalAssets,
 }
 
+pub const ANIMATIONS_PATH: &str = "animations.json";
+
 impl AnimationConfiguration {
-    pub fn new() -> Self {
+    /// Reads the animation sheets from [`ANIMATIONS_PATH`] and checks that every animation fits
+    /// in its atlas.
+    pub fn load() -> Self {
+        let path = FileAssetIo::get_base_path().join("assets").join(ANIMATIONS_PATH);
+        let contents = std::fs::read_to_string(&path).unwrap_or_else(|error| {
+            panic!("could not read animation sheets from {}: {}", path.display(), error)
+        });
+        let sheets: std::collections::HashMap<String, AnimationSheet> = serde_json::from_str(&contents)
+            .unwrap_or_else(|error| panic!("invalid animation sheets in {}: {}", path.display(), error));
+        let mut errors: Vec<String> = sheets.iter()
+            .flat_map(|(name, sheet)| sheet.validate().into_iter().map(move |error| format!("{}: {}", name, error)))
+            .collect();
+        if !errors.is_empty() {
+            errors.sort();
+            panic!("invalid animation sheets in {}:\n{}", path.display(), errors.join("\n"));
+        }
         Self {
-            player: AnimationSheet {
-                sprite_sheet: String::from("collie_sheet.png"),
-                sprite_sheet_handle: None,
-                atlas_tile_columns: 4,
-                atlas_tile_rows: 4,
-                texture_size: Vec2::new(20.0, 16.0),
-                snappy_animations: true,
-                animation_class: AnimationClass::Actor {
-                    run_threshold_fraction: 0.3,
-                    flip_threshold_fraction: 0.1,
-                    idle: SingleAnimation {
-                        animation_interval: 0.3,
-                        first_index: 0,
-                        last_index: 2,
-                    },
-                    running: SingleAnimation {
-                        animation_interval: 0.15,
-                        first_index: 4,
-                        last_index: 5,
-                    },
-                },
-            },
-
-            sheep: AnimationSheet {
-                sprite_sheet: String::from("sheep_sheet.png"),
-                sprite_sheet_handle: None,
-                atlas_tile_columns: 6,
-                atlas_tile_rows: 3,
-                texture_size: Vec2::new(16.0, 16.0),
-                snappy_animations: false,
-                animation_class: AnimationClass::Actor {
-                    run_threshold_fraction: 0.3,
-                    flip_threshold_fraction: 0.2,
-                    idle: SingleAnimation {
-                        animation_interval: 0.3,
-                        first_index: 0,
-                        last_index: 2,
-                    },
-                    running: SingleAnimation {
-                        animation_interval: 0.15,
-                        first_index: 7,
-                        last_index: 10,
-                    },
-                },
-            },
-            fence_horizontal: AnimationSheet {
-                sprite_sheet: String::from("fence_horizontal.png"),
-                sprite_sheet_handle: None,
-                atlas_tile_columns: 1,
-                atlas_tile_rows: 1,
-                texture_size: Vec2::new(19.0, 8.0),
-                snappy_animations: false,
-                animation_class: AnimationClass::Simple {
-                    simple: SingleAnimation {
-                        animation_interval: 1.0,
-                        first_index: 0,
-                        last_index: 0,
-                    },
-                },
-            },
-            fence_vertical: AnimationSheet {
-                sprite_sheet: String::from("fence_vertical.png"),
-                sprite_sheet_handle: None,
-                atlas_tile_columns: 1,
-                atlas_tile_rows: 1,
-                texture_size: Vec2::new(2.0, 26.0),
-                snappy_animations: false,
-                animation_class: AnimationClass::Simple {
-                    simple: SingleAnimation {
-                        animation_interval: 1.0,
-                        first_index: 0,
-                        last_index: 0,
-                    },
-                },
-            },
-            grass: AnimationSheet {
-                sprite_sheet: String::from("spritesheet.png"),
-                sprite_sheet_handle: None,
-                atlas_tile_columns: 4,
-                atlas_tile_rows: 4,
-                texture_size: Vec2::new(16.0, 16.0),
-                snappy_animations: false,
-                animation_class: AnimationClass::Simple {
-                    simple: SingleAnimation {
-                        animation_interval: 0.7,
-                        first_index: 8,
-                        last_index: 8,
-                    },
-                },
-            },
-            food: AnimationSheet {
-                sprite_sheet: String::from("spritesheet.png"),
-                sprite_sheet_handle: None,
-                atlas_tile_columns: 4,
-                atlas_tile_rows: 4,
-                texture_size: Vec2::new(16.0, 16.0),
-                snappy_animations: false,
-                animation_class: AnimationClass::Simple {
-                    simple: SingleAnimation {
-                        animation_interval: 1.0,
-                        first_index: 15,
-                        last_index: 15,
-                    }
-                },
-            },
+            sheets: sheets.into_iter().collect(),
         }
     }
-    pub fn get_set(self: &Self, id: &ConfigurationSetId) -> &AnimationSheet {
-        match id {
-            ConfigurationSetId::Player => {
-                &self.player
-            }
-            ConfigurationSetId::Sheep => {
-                &self.sheep
-            }
-            ConfigurationSetId::Grass => {
-                &self.grass
-            }
-            ConfigurationSetId::FenceHorizontal => {
-                &self.fence_horizontal
-            }
-            ConfigurationSetId::FenceVertical => {
-                &self.fence_vertical
-            }
-            ConfigurationSetId::Food => {
-                &self.food
-            }
-        }
+
+    pub fn get_sheet(&self, name: &str) -> &AnimationSheet {
+        self.sheets.get(name).unwrap_or_else(|| {
+            let mut known: Vec<&String> = self.sheets.keys().collect();
+            known.sort();
+            panic!("unknown animation sheet \"{}\", the known sheets are {:?}", name, known)
+        })
     }
 }
 
@@ -222,55 +342,52 @@ pub fn load_sprite_sheets(
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     mut config: ResMut<Configuration>,
 ) {
-    let mut anim_config = &mut config.animation;
-    macro_rules! load {
-        ($name:ident, $anim:ident) => {
-            let texture_handle = asset_server.load(&anim_config.$name.sprite_sheet);
-            let mut texture_atlas = TextureAtlas::from_grid(
-                texture_handle,
-                anim_config.$name.texture_size,
-                anim_config.$name.atlas_tile_columns,
-                anim_config.$name.atlas_tile_rows,
-                None,
-                None,
-            );
-            for mut rect in texture_atlas.textures.iter_mut() {
-                rect.min = rect.min - 0.5;
-                rect.max = rect.max - 0.5;
-            }
-            let texture_atlas_handle = texture_atlases.add(texture_atlas);
-            anim_config.$name.sprite_sheet_handle = Some(texture_atlas_handle);
-        };
+    load_missing_sprite_sheets(&asset_server, &mut texture_atlases, &mut config);
+}
+
+/// Builds texture atlases for every animation sheet that does not have one yet.
+pub fn load_missing_sprite_sheets(
+    asset_server: &AssetServer,
+    texture_atlases: &mut Assets<TextureAtlas>,
+    config: &mut Configuration,
+) {
+    for sheet in config.animation.sheets.values_mut() {
+        if sheet.sprite_sheet_handle.is_some() { continue; }
+        let texture_handle = asset_server.load(&sheet.sprite_sheet);
+        let mut texture_atlas = TextureAtlas::from_grid(
+            texture_handle,
+            sheet.texture_size,
+            sheet.atlas_tile_columns,
+            sheet.atlas_tile_rows,
+            None,
+            None,
+        );
+        for mut rect in texture_atlas.textures.iter_mut() {
+            rect.min = rect.min - 0.5;
+            rect.max = rect.max - 0.5;
+        }
+        sheet.sprite_sheet_handle = Some(texture_atlases.add(texture_atlas));
+    }
+    if config.global_assets.font.is_none() {
+        config.global_assets.font = Some(asset_server.load(&config.global_assets.font_path));
     }
-    load!(player, idle);
-    load!(player, running);
-    load!(sheep, idle);
-    load!(sheep, running);
-    load!(fence_horizontal, simple);
-    load!(fence_vertical, simple);
-    load!(grass, simple);
-    load!(food, simple);
-    config.global_assets.font = Some(asset_server.load(&config.global_assets.font_path));
 }
 
 
-#[derive(Reflect, Default, Resource, InspectorOptions)]
+#[derive(Reflect, Default, Resource, InspectorOptions, serde::Serialize, serde::Deserialize)]
 #[reflect(Resource, InspectorOptions)]
 pub struct AnimationConfiguration {
-    pub player: AnimationSheet,
-    pub sheep: AnimationSheet,
-    pub fence_horizontal: AnimationSheet,
-    pub fence_vertical: AnimationSheet,
-    pub grass: AnimationSheet,
-    pub food: AnimationSheet,
+    /// Referenced by name from `ConfigurationSet::animation_sheet`.
+    pub sheets: HashMap<String, AnimationSheet>,
 }
 
-#[derive(Reflect, Default, Resource, InspectorOptions)]
+#[derive(Reflect, FromReflect, Default, Resource, InspectorOptions, serde::Serialize, serde::Deserialize)]
 #[reflect(Resource, InspectorOptions)]
 pub struct AnimationSheet {
     #[reflect(ignore)]
     pub sprite_sheet: String,
     #[reflect(ignore)]
+    #[serde(skip)]
     pub sprite_sheet_handle: Option<Handle<TextureAtlas>>,
     #[reflect(ignore)]
     pub atlas_tile_columns: usize,
@@ -278,67 +395,230 @@ pub struct AnimationSheet {
     pub atlas_tile_rows: usize,
     #[reflect(ignore)]
     pub texture_size: Vec2,
-    pub snappy_animations: bool,
-    // Should we change animations frame-perfectly or wait until the next?
-    pub animation_class: AnimationClass,
+    /// The state entities start in. Also where one-shot animations return to if there is no
+    /// previous state.
+    #[serde(default = "AnimationSheet::default_initial_animation")]
+    pub initial_animation: String,
+    /// Set for sheets that face the direction they move in.
+    #[serde(default)]
+    pub movement: Option<MovementAnimation>,
+    /// Every state of the state machine, keyed by name.
+    pub animations: HashMap<String, SingleAnimation>,
+    /// Checked in order every frame, the first one that applies is taken.
+    #[reflect(ignore)]
+    #[serde(default)]
+    pub transitions: Vec<AnimationTransition>,
 }
 
+impl AnimationSheet {
+    fn default_initial_animation() -> String {
+        String::from(animation::IDLE_ANIMATION)
+    }
 
-#[derive(Reflect, Resource, InspectorOptions)]
-#[reflect(Resource, InspectorOptions)]
-pub enum AnimationClass {
-    Simple {
-        simple: SingleAnimation,
-    },
-    Actor {
-        idle: SingleAnimation,
-        running: SingleAnimation,
-        run_threshold_fraction: f32,
-        flip_threshold_fraction: f32,
-    },
-}
-
-impl Default for AnimationClass {
-    fn default() -> Self {
-        AnimationClass::Simple { simple: SingleAnimation::default() }
+    pub fn num_frames(&self) -> usize {
+        self.atlas_tile_columns * self.atlas_tile_rows
     }
+
+    /// Every problem with the sheet, empty if it is valid.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.num_frames() == 0 {
+            errors.push(format!("{} has no frames", self.sprite_sheet));
+        }
+        if !self.animations.contains_key(&self.initial_animation) {
+            errors.push(format!("missing initial animation \"{}\"", self.initial_animation));
+        }
+        for (name, animation) in self.animations.iter() {
+            if animation.first_index > animation.last_index {
+                errors.push(format!(
+                    "animation \"{}\" starts at frame {} after its last frame {}",
+                    name, animation.first_index, animation.last_index));
+            } else if animation.last_index >= self.num_frames() {
+                errors.push(format!(
+                    "animation \"{}\" uses frames {}..={} but {} only has {} frames",
+                    name, animation.first_index, animation.last_index, self.sprite_sheet, self.num_frames()));
+            }
+            if animation.animation_interval < 0.0 {
+                errors.push(format!("animation \"{}\" has a negative interval", name));
+            }
+            if let Some(playback) = &animation.playback {
+                if playback.min_rate <= 0.0 || playback.max_rate < playback.min_rate {
+                    errors.push(format!(
+                        "animation \"{}\" needs a playback curve with 0 < min_rate <= max_rate", name));
+                }
+            }
+        }
+        for transition in self.transitions.iter() {
+            for state in transition.from.iter().chain(std::iter::once(&transition.to)) {
+                if !self.animations.contains_key(state) {
+                    errors.push(format!("transition to \"{}\" uses unknown state \"{}\"", transition.to, state));
+                }
+            }
+        }
+        errors
+    }
+}
+
+#[derive(Reflect, FromReflect, Clone, Resource, InspectorOptions, serde::Serialize, serde::Deserialize)]
+#[reflect(Resource, InspectorOptions)]
+pub struct MovementAnimation {
+    pub flip_threshold_fraction: f32,
+    /// Turn around immediately instead of at the end of the current frame.
+    #[serde(default)]
+    pub snappy_flip: bool,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct AnimationTransition {
+    /// States the transition can be taken from. If empty it applies to every state except
+    /// one-shot states, which only end early through transitions that name them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub from: Vec<String>,
+    pub to: String,
+    pub when: AnimationCondition,
+    /// Switch frame-perfectly instead of waiting for the current frame to finish.
+    #[serde(default)]
+    pub snappy: bool,
 }
 
-#[derive(Reflect, Default, Resource, InspectorOptions)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnimationCondition {
+    Always,
+    /// Moving faster than this fraction of max speed.
+    SpeedAbove(f32),
+    SpeedBelow(f32),
+    /// Standing still to graze, see [`sheep::Grazing`].
+    Grazing,
+    /// Running away with at least this [`sheep::Runner`] magnitude.
+    Scared(f32),
+    /// Barked this frame. Only true for a moment, so transitions on it should be snappy.
+    Barked,
+    /// Been in the current state for at least this many seconds.
+    InStateFor(f32),
+    All(Vec<AnimationCondition>),
+    Not(Box<AnimationCondition>),
+}
+
+#[derive(Reflect, FromReflect, Default, Resource, InspectorOptions, serde::Serialize, serde::Deserialize)]
 #[reflect(Resource, InspectorOptions)]
 pub struct SingleAnimation {
     pub animation_interval: f32,
     pub first_index: usize,
     pub last_index: usize,
+    /// Plays once and then returns to the state it was entered from.
+    #[serde(default)]
+    pub one_shot: bool,
+    /// Replaces `animation_interval` with a frame rate that follows the speed of the entity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub playback: Option<PlaybackCurve>,
 }
 
-impl FromReflect for SingleAnimation {
-    fn from_reflect(_reflect: &dyn Reflect) -> Option<Self> {
-        None
+/// Frames per second as `scale` times the fraction of max speed the entity moves at, kept
+/// between `min_rate` and `max_rate`.
+#[derive(Reflect, FromReflect, Clone, Resource, InspectorOptions, serde::Serialize, serde::Deserialize)]
+#[reflect(Resource, InspectorOptions)]
+pub struct PlaybackCurve {
+    #[inspector(min = 0.0)]
+    pub scale: f32,
+    #[inspector(min = 0.0)]
+    pub min_rate: f32,
+    #[inspector(min = 0.0)]
+    pub max_rate: f32,
+}
+
+impl PlaybackCurve {
+    pub fn frame_rate(&self, speed_fraction: f32) -> f32 {
+        (speed_fraction * self.scale).clamp(self.min_rate, self.max_rate)
     }
 }
 
 
-#[derive(Component)]
-pub enum ConfigurationSetId {
-    Player,
-    Sheep,
-    FenceHorizontal,
-    FenceVertical,
-    Grass,
-    Food,
+/// Name of the configuration set in `Configuration::sets` that an entity takes its stats from.
+#[derive(Component, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ConfigurationSetId(pub String);
+
+impl ConfigurationSetId {
+    pub fn new(name: &str) -> Self {
+        ConfigurationSetId(String::from(name))
+    }
 }
 
-#[derive(Reflect, Default, InspectorOptions)]
+pub const CONFIGURATION_SETS_PATH: &str = "configuration_sets.json";
+
+/// Sets the game spawns entities from by default, which therefore have to exist.
+pub const REQUIRED_SETS: &[&str] = &[
+    player::CONFIG_SET,
+    sheep::CONFIG_SET,
+    wolf::CONFIG_SET,
+    fence::HORIZONTAL_CONFIG_SET,
+    fence::VERTICAL_CONFIG_SET,
+    grass::CONFIG_SET,
+    food::CONFIG_SET,
+];
+
+#[derive(Reflect, FromReflect, Default, InspectorOptions, serde::Serialize, serde::Deserialize)]
 #[reflect(InspectorOptions)]
 pub struct ConfigurationSet {
+    #[serde(default)]
     #[inspector(min = 0.0)]
     pub max_speed: f32,
+    #[serde(default)]
+    #[inspector(min = 0.0)]
+    pub collider_radius: f32,
+    /// Name of the sheet in `AnimationConfiguration::sheets`.
+    pub animation_sheet: String,
+    #[serde(default)]
+    pub behaviours: Behaviours,
 }
 
+/// Which behaviour components an actor spawned from a set gets.
+#[derive(Reflect, FromReflect, Default, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Behaviours {
+    pub flocking: bool,
+    pub grazing: bool,
+    /// Runs from dogs and wolves, and panics along with its neighbours.
+    pub runner: bool,
+    pub inertia: bool,
+    pub likes_food: bool,
+    pub counts_toward_goal: bool,
+    /// Gets hungry and tired, see [`needs::Needs`].
+    pub needs: bool,
+}
+
+/// Reads the configuration sets from the assets folder and checks that the [`REQUIRED_SETS`]
+/// exist and every set names a known animation sheet. They are needed to spawn anything, so a
+/// missing or broken file is a fatal error.
+pub fn load_configuration_sets(animation: &AnimationConfiguration) -> HashMap<String, ConfigurationSet> {
+    let path = FileAssetIo::get_base_path().join("assets").join(CONFIGURATION_SETS_PATH);
+    let contents = std::fs::read_to_string(&path).unwrap_or_else(|error| {
+        panic!("could not read configuration sets from {}: {}", path.display(), error)
+    });
+    let sets: std::collections::HashMap<String, ConfigurationSet> = serde_json::from_str(&contents)
+        .unwrap_or_else(|error| panic!("invalid configuration sets in {}: {}", path.display(), error));
+    let mut errors: Vec<String> = REQUIRED_SETS.iter()
+        .filter(|name| !sets.contains_key(**name))
+        .map(|name| format!("missing the set \"{}\"", name))
+        .collect();
+    for (name, set) in sets.iter() {
+        if !animation.sheets.contains_key(&set.animation_sheet) {
+            errors.push(format!(
+                "{}: unknown animation sheet \"{}\", see {}", name, set.animation_sheet, ANIMATIONS_PATH,
+            ));
+        }
+    }
+    if !errors.is_empty() {
+        errors.sort();
+        panic!("invalid configuration sets in {}:\n{}", path.display(), errors.join("\n"));
+    }
+    sets.into_iter().collect()
+}
 
-#[derive(Reflect, Default, Resource, InspectorOptions)]
+
+#[derive(Reflect, Default, Clone, PartialEq, Resource, InspectorOptions, serde::Serialize, serde::Deserialize)]
 #[reflect(Resource, InspectorOptions)]
+#[serde(default = "FlockingConfiguration::built_in")]
 pub struct FlockingConfiguration {
     pub alignment_enabled: bool,
     #[inspector(min = 0.0)]
@@ -358,10 +638,28 @@ pub struct FlockingConfiguration {
     pub separation_distance: f32,
     #[inspector(min = 0.0)]
     pub separation_scale: f32,
+    pub avoidance_enabled: bool,
+    /// How far ahead sheep look for fences to steer around.
+    #[inspector(min = 0.0)]
+    pub avoidance_distance: f32,
+    #[inspector(min = 0.0)]
+    pub avoidance_scale: f32,
 }
 
-#[derive(Reflect, Default, InspectorOptions)]
+impl FlockingConfiguration {
+    /// The largest distance at which any enabled flocking behaviour looks for neighbours.
+    pub fn max_neighbour_distance(&self) -> f32 {
+        let mut distance: f32 = 0.0;
+        if self.alignment_enabled { distance = distance.max(self.alignment_distance); }
+        if self.cohesion_enabled { distance = distance.max(self.cohesion_distance); }
+        if self.separation_enabled { distance = distance.max(self.separation_distance); }
+        distance
+    }
+}
+
+#[derive(Reflect, Default, Clone, PartialEq, InspectorOptions, serde::Serialize, serde::Deserialize)]
 #[reflect(InspectorOptions)]
+#[serde(default = "RunnerConfiguration::built_in")]
 pub struct RunnerConfiguration {
     #[inspector(min = 0.0)]
     pub scale: f32,
@@ -369,31 +667,333 @@ pub struct RunnerConfiguration {
     pub speed_fraction: f32,
     #[inspector(min = 0.0)]
     pub scare_distance: f32,
+    /// Dogs moving slower than this fraction of their max speed are approaching calmly and
+    /// scare runners from a shorter distance. 0.0 disables it.
+    #[inspector(min = 0.0, max = 1.0)]
+    pub calm_speed_fraction: f32,
+    /// Fraction of the scare distance left for a dog that stands completely still.
+    #[inspector(min = 0.0, max = 1.0)]
+    pub calm_scare_fraction: f32,
 }
 
-#[derive(Reflect, Default)]
-pub enum DebugLineType {
+impl RunnerConfiguration {
+    /// How much of the scare distance applies to a dog moving at `speed_fraction` of its max speed.
+    pub fn approach_scale(&self, speed_fraction: f32) -> f32 {
+        if self.calm_speed_fraction <= 0.0 || speed_fraction >= self.calm_speed_fraction {
+            return 1.0;
+        }
+        self.calm_scare_fraction + (1.0 - self.calm_scare_fraction) * speed_fraction / self.calm_speed_fraction
+    }
+}
+
+#[derive(Reflect, Default, InspectorOptions, serde::Serialize, serde::Deserialize)]
+#[reflect(InspectorOptions)]
+#[serde(default = "StaminaConfiguration::built_in")]
+pub struct StaminaConfiguration {
+    pub enabled: bool,
+    /// Max speed multiplier while sprinting.
+    #[inspector(min = 1.0)]
+    pub sprint_speed: f32,
+    /// Stamina used per second of sprinting, out of a full 1.0.
+    #[inspector(min = 0.0)]
+    pub drain_rate: f32,
+    /// Stamina regained per second while not sprinting.
+    #[inspector(min = 0.0)]
+    pub regen_rate: f32,
+    /// Stamina needed to start a new sprint.
+    #[inspector(min = 0.0, max = 1.0)]
+    pub min_to_sprint: f32,
+}
+
+#[derive(Reflect, Default, InspectorOptions, serde::Serialize, serde::Deserialize)]
+#[reflect(InspectorOptions)]
+#[serde(default = "AutopilotConfiguration::built_in")]
+pub struct AutopilotConfiguration {
+    /// Keys that hand every dog to the autopilot and back, to show how a level can be solved.
+    pub toggle: Vec<KeyCode>,
+    /// How far behind a stray sheep the dog goes to bring it back to the flock.
+    #[inspector(min = 0.0)]
+    pub collect_distance: f32,
+    /// Spacing of sheep in a tight flock. The flock counts as gathered while every sheep is
+    /// within this times N^(2/3) of its centre, and is driven from this times sqrt(N) behind.
+    #[inspector(min = 0.0)]
+    pub interaction_distance: f32,
+    /// Within this distance of any sheep the dog slows down so it does not scatter the flock.
+    #[inspector(min = 0.0)]
+    pub slow_distance: f32,
+    #[inspector(min = 0.0, max = 1.0)]
+    pub slow_speed: f32,
+    /// The dog starts slowing down within this distance of where it wants to be.
+    #[inspector(min = 0.0)]
+    pub arrival_distance: f32,
+}
+
+#[derive(Reflect, Default, InspectorOptions, serde::Serialize, serde::Deserialize)]
+#[reflect(InspectorOptions)]
+#[serde(default = "HuntingConfiguration::built_in")]
+pub struct HuntingConfiguration {
+    /// How close a wolf has to be for sheep to run from it.
+    #[inspector(min = 0.0)]
+    pub scare_distance: f32,
+    /// Sheep further than this from the centre of the flock are considered strays and hunted.
+    #[inspector(min = 0.0)]
+    pub isolation_distance: f32,
+    #[inspector(min = 0.0)]
+    pub stalk_speed_fraction: f32,
+    /// Within this distance of its target, the wolf stops stalking and runs at full speed.
+    #[inspector(min = 0.0)]
+    pub attack_distance: f32,
+    #[inspector(min = 0.0)]
+    pub catch_distance: f32,
+    #[inspector(min = 0.0)]
+    pub dog_flee_distance: f32,
+    #[inspector(min = 0.0)]
+    pub flee_time: f32,
+}
+
+#[derive(Reflect, Default, InspectorOptions, serde::Serialize, serde::Deserialize)]
+#[reflect(InspectorOptions)]
+#[serde(default = "BarkConfiguration::built_in")]
+pub struct BarkConfiguration {
+    #[inspector(min = 0.0)]
+    pub cooldown: f32,
+    #[inspector(min = 0.0)]
+    pub radius: f32,
+    /// Runner magnitude given to every sheep within the radius.
+    #[inspector(min = 0.0)]
+    pub magnitude: f32,
+}
+
+/// Keys a preset leaves out are left unbound.
+#[derive(Reflect, FromReflect, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct PlayerBindings {
+    pub up: Vec<KeyCode>,
+    pub down: Vec<KeyCode>,
+    pub left: Vec<KeyCode>,
+    pub right: Vec<KeyCode>,
+    pub bark: Vec<KeyCode>,
+    pub sprint: Vec<KeyCode>,
+    /// Id of the gamepad that also controls this player, if any.
+    pub gamepad: Option<usize>,
+    pub scheme: ControlScheme,
+    pub tint: Color,
+}
+
+#[derive(Reflect, FromReflect, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum ControlScheme {
     #[default]
-    None,
-    AlignmentInfluence,
-    CohesionInfluence,
-    SeparationInfluence,
-    RunnerInfluence,
-    RunnerUnmodifiedInfluence,
-    RunnerMaxInfluence,
-    GrazingInfluence,
-    InertiaInfluence,
-    TotalInfluence,
-    MaxInfluence,
+    KeysAndGamepad,
+    /// The dog follows the cursor, the keys are ignored apart from barking.
+    Mouse,
 }
 
-#[derive(Reflect, Default)]
+#[derive(Reflect, Default, InspectorOptions, serde::Serialize, serde::Deserialize)]
+#[reflect(InspectorOptions)]
+#[serde(default = "MouseConfiguration::built_in")]
+pub struct MouseConfiguration {
+    /// Within this distance of the cursor, the dog slows down proportionally.
+    #[inspector(min = 0.0)]
+    pub arrival_distance: f32,
+    #[inspector(min = 0.0)]
+    pub stop_distance: f32,
+}
+
+#[derive(Reflect, Default, InspectorOptions, serde::Serialize, serde::Deserialize)]
+#[reflect(InspectorOptions)]
+#[serde(default = "GamepadConfiguration::built_in")]
+pub struct GamepadConfiguration {
+    /// Stick deflections shorter than this are ignored, measured radially.
+    #[inspector(min = 0.0, max = 1.0)]
+    pub dead_zone: f32,
+    pub buttons: GamepadButtonBindings,
+}
+
+/// Gamepad buttons for each player action, shared by all gamepads.
+#[derive(Reflect, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct GamepadButtonBindings {
+    pub bark: GamepadButtonType,
+    pub hold_position: GamepadButtonType,
+    pub sprint: GamepadButtonType,
+}
+
+impl Default for GamepadButtonBindings {
+    fn default() -> Self {
+        GamepadButtonBindings {
+            bark: GamepadButtonType::South,
+            hold_position: GamepadButtonType::West,
+            sprint: GamepadButtonType::RightTrigger,
+        }
+    }
+}
+
+#[derive(Reflect, Default, InspectorOptions, serde::Serialize, serde::Deserialize)]
+#[reflect(InspectorOptions)]
+#[serde(default = "PanicConfiguration::built_in")]
+pub struct PanicConfiguration {
+    pub enabled: bool,
+    /// Fear only spreads to neighbours within this distance.
+    #[inspector(min = 0.0)]
+    pub spread_radius: f32,
+    /// How fast a sheep takes on the fear of its most frightened neighbour, per second.
+    #[inspector(min = 0.0)]
+    pub spread_rate: f32,
+    /// Fear lost per second.
+    #[inspector(min = 0.0)]
+    pub decay_rate: f32,
+}
+
+#[derive(Reflect, Default, InspectorOptions, serde::Serialize, serde::Deserialize)]
+#[reflect(InspectorOptions)]
+#[serde(default = "GrassConfiguration::built_in")]
+pub struct GrassConfiguration {
+    /// When disabled, grass is only decoration and grazers wander at random.
+    pub enabled: bool,
+    /// Grazers standing still eat the nearest grass within this distance.
+    #[inspector(min = 0.0)]
+    pub eat_radius: f32,
+    /// Amount eaten per second, out of a full 1.0.
+    #[inspector(min = 0.0)]
+    pub eat_rate: f32,
+    /// Amount regrown per second.
+    #[inspector(min = 0.0)]
+    pub regrow_rate: f32,
+    /// How far grazers look for uneaten grass.
+    #[inspector(min = 1.0)]
+    pub seek_distance: f32,
+    /// 0.0 wanders at random, 1.0 heads straight for the best grass.
+    #[inspector(min = 0.0, max = 1.0)]
+    pub seek_bias: f32,
+    #[inspector(min = 0.0, max = 1.0)]
+    pub grazed_below: f32,
+    #[inspector(min = 0.0, max = 1.0)]
+    pub bare_below: f32,
+}
+
+#[derive(Reflect, Default, InspectorOptions, serde::Serialize, serde::Deserialize)]
+#[reflect(InspectorOptions)]
+#[serde(default = "NeedsConfiguration::built_in")]
+pub struct NeedsConfiguration {
+    pub enabled: bool,
+    /// Hunger gained per second.
+    #[inspector(min = 0.0)]
+    pub hunger_rate: f32,
+    /// Hunger lost per second while at a food source.
+    #[inspector(min = 0.0)]
+    pub food_nourishment: f32,
+    /// Hunger lost per full amount of grass eaten.
+    #[inspector(min = 0.0)]
+    pub grass_nourishment: f32,
+    /// How much a completely hungry animal ignores threats while eating. At 1.0 it does not
+    /// move from its food at all.
+    #[inspector(min = 0.0, max = 1.0)]
+    pub hunger_resistance: f32,
+    /// Energy lost per second while running at full runner magnitude.
+    #[inspector(min = 0.0)]
+    pub energy_drain: f32,
+    /// Energy gained per second while resting.
+    #[inspector(min = 0.0)]
+    pub energy_recovery: f32,
+    /// Moving slower than this fraction of max speed counts as resting.
+    #[inspector(min = 0.0, max = 1.0)]
+    pub rest_speed_fraction: f32,
+    /// Animals with less energy than this start slowing down.
+    #[inspector(min = 0.0, max = 1.0)]
+    pub exhausted_below: f32,
+    /// Fraction of max speed left with no energy at all.
+    #[inspector(min = 0.0, max = 1.0)]
+    pub exhausted_speed: f32,
+}
+
+#[derive(Reflect, Default, InspectorOptions, serde::Serialize, serde::Deserialize)]
+#[reflect(InspectorOptions)]
+#[serde(default = "WindConfiguration::built_in")]
+pub struct WindConfiguration {
+    pub enabled: bool,
+    /// Direction the wind blows towards, in degrees counter-clockwise from the x axis.
+    pub direction: f32,
+    /// How fast gusts travel across the level, in pixels per second.
+    #[inspector(min = 0.0)]
+    pub speed: f32,
+    /// Strength everywhere, before gusts.
+    #[inspector(min = 0.0)]
+    pub base_strength: f32,
+    /// Extra strength at the centre of a gust.
+    #[inspector(min = 0.0)]
+    pub gust_strength: f32,
+    /// Rough diameter of a gust, in pixels.
+    #[inspector(min = 1.0)]
+    pub gust_size: f32,
+    /// Sways of the grass per second.
+    #[inspector(min = 0.0)]
+    pub sway_frequency: f32,
+    /// Grass is drawn bent while it sways further than this.
+    #[inspector(min = 0.0)]
+    pub sway_threshold: f32,
+    /// Whether gusts push sheep around.
+    pub influence_enabled: bool,
+    /// Only gusts stronger than this push sheep.
+    #[inspector(min = 0.0)]
+    pub influence_threshold: f32,
+    #[inspector(min = 0.0)]
+    pub influence_scale: f32,
+}
+
+#[derive(Reflect, Default, InspectorOptions, serde::Serialize, serde::Deserialize)]
+#[reflect(InspectorOptions)]
+#[serde(default = "TemperamentConfiguration::built_in")]
+pub struct TemperamentConfiguration {
+    /// When disabled, every sheep gets the average temperament unless the level overrides it.
+    pub enabled: bool,
+    pub boldness: TraitDistribution,
+    pub sociability: TraitDistribution,
+    pub speed: TraitDistribution,
+    pub wanderlust: TraitDistribution,
+}
+
+/// Presets have to give all four values, a distribution is not filled in from the built-in one.
+#[derive(Reflect, Default, InspectorOptions, serde::Serialize, serde::Deserialize)]
+#[reflect(InspectorOptions)]
+pub struct TraitDistribution {
+    pub mean: f32,
+    #[inspector(min = 0.0)]
+    pub std_dev: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl TraitDistribution {
+    pub fn sample(&self, rng: &mut impl Rng) -> f32 {
+        let value = Normal::new(self.mean, self.std_dev)
+            .map(|distribution| distribution.sample(rng))
+            .unwrap_or(self.mean);
+        value.max(self.min).min(self.max)
+    }
+}
+
+#[derive(Reflect, Default, InspectorOptions, serde::Serialize, serde::Deserialize)]
+#[reflect(InspectorOptions)]
+#[serde(default = "FoodConfiguration::built_in")]
+pub struct FoodConfiguration {
+    pub enabled: bool,
+    #[inspector(min = 0.0)]
+    pub attraction_distance: f32,
+    /// Closer than this to the food, sheep are eating and no longer pulled toward it.
+    #[inspector(min = 0.0)]
+    pub satisfied_distance: f32,
+    #[inspector(min = 0.0)]
+    pub scale: f32,
+}
+
+#[derive(Reflect, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default = "DebugLineConfiguration::built_in")]
 pub struct DebugLineConfiguration {
+    /// Toggled while playing or with `--debug-lines`, so it is not part of presets.
+    #[serde(skip)]
     pub enable: bool,
-    pub red: DebugLineType,
-    pub green: DebugLineType,
-    pub blue: DebugLineType,
-    pub gray: DebugLineType,
+    /// Names of the registered influences to draw, each in its own debug colour.
+    pub influences: Vec<String>,
 }
 
 #[derive(Reflect, Default)]
@@ -402,4 +1002,21 @@ pub struct GlobalAssets {
     pub font: Option<Handle<Font>>,
     pub level_index: Option<Handle<YoleckLevelIndex>>,
     pub levels: Vec<Handle<YoleckRawLevel>>,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_preset_keeps_built_in_values() {
+        let preset = r#"{ "zoom": 2.0, "flocking": { "cohesion_scale": 3.0 }, "gamepad": { "dead_zone": 0.3 } }"#;
+        let config = presets::parse_preset(preset).unwrap();
+        let built_in = Configuration::built_in_tuning();
+        assert_eq!(config.zoom, 2.0);
+        assert_eq!(config.flocking, FlockingConfiguration { cohesion_scale: 3.0, ..built_in.flocking });
+        assert_eq!(config.gamepad.dead_zone, 0.3);
+        assert!(config.gamepad.buttons.bark == built_in.gamepad.buttons.bark);
+        assert!(config.runner == built_in.runner);
+        assert_eq!(config.grass.seek_distance, built_in.grass.seek_distance);
+    }
+}
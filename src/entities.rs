@@ -1,4 +1,5 @@
 pub mod sheep;
+pub mod autopilot;
 pub mod fence;
 pub mod grass;
 pub mod player;
@@ -7,9 +8,36 @@ pub mod text;
 pub mod food;
 pub mod zone;
 pub mod game_rules;
+pub mod level_settings;
+pub mod needs;
+pub mod wolf;
 
+use bevy::ecs::system::EntityCommands;
 use crate::imports::*;
 
+/// Lets a level pick which configuration set an entity takes its stats from. `None` keeps the
+/// default set of its type, `default_set`.
+pub fn configuration_set_ui(ui: &mut egui::Ui, config_set: &mut Option<String>, config: &Configuration, default_set: &str) {
+    let mut names: Vec<&String> = config.sets.keys().collect();
+    names.sort();
+    let default_label = format!("Default ({})", default_set);
+    ui.horizontal(|ui| {
+        ui.add(egui::Label::new("Configuration set: "));
+        egui::ComboBox::from_id_source("configuration_set")
+            .selected_text(config_set.as_deref().unwrap_or(&default_label))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(config_set, None, &default_label);
+                for name in names {
+                    ui.selectable_value(config_set, Some(name.clone()), name.as_str());
+                }
+            });
+        // Entities with an unknown set are not spawned, see `Configuration::set_id_or`
+        if config_set.as_ref().map_or(false, |name| !config.sets.contains_key(name)) {
+            ui.colored_label(egui::Color32::RED, "Unknown set");
+        }
+    });
+}
+
 #[derive(Bundle)]
 pub struct Actor {
     pub animation_bundle: AnimationBundle,
@@ -18,6 +46,7 @@ pub struct Actor {
     pub locked_axes: LockedAxes,
     pub velocity: Velocity,
     pub influences: Influences,
+    pub speed_modifier: SpeedModifier,
 }
 
 impl Actor {
@@ -29,8 +58,39 @@ impl Actor {
             locked_axes: LockedAxes::ROTATION_LOCKED,
             velocity: Velocity::default(),
             influences: Influences::default(),
+            speed_modifier: SpeedModifier::default(),
             collider,
         }
     }
 }
 
+impl Behaviours {
+    /// Adds the components of every enabled behaviour to an entity.
+    pub fn insert_into(&self, commands: &mut EntityCommands, rng: &mut impl Rng) {
+        if self.flocking {
+            commands.insert(sheep::Flocking::default());
+        }
+        if self.grazing {
+            commands.insert(sheep::Grazing {
+                current_direction: None,
+                time_left: (rng.gen::<f32>() * 5.0) + 0.0,
+            });
+        }
+        if self.runner {
+            commands.insert((sheep::Runner::default(), sheep::Fear::default()));
+        }
+        if self.inertia {
+            commands.insert(sheep::Inertia::default());
+        }
+        if self.likes_food {
+            commands.insert(food::LikesFood);
+        }
+        if self.counts_toward_goal {
+            commands.insert(zone::CountsTowardGoal);
+        }
+        if self.needs {
+            commands.insert(needs::Needs::default());
+        }
+    }
+}
+
@@ -0,0 +1,183 @@
+//! A computer controlled sheepdog following the shepherding model of Strömbom et al. (2014),
+//! "Solving the shepherding problem: heuristics for herding autonomous, interacting agents".
+//! While any sheep has strayed too far from the flock the dog collects it by getting behind it,
+//! otherwise it drives the whole flock from behind toward the goal. With several dogs the first
+//! one always drives, and the others fetch the strays or help by flanking the flock.
+
+use crate::imports::*;
+
+#[derive(Default)]
+pub struct AutopilotPlugin {
+    /// Start with every dog on autopilot, e.g. for a demo.
+    pub enabled: bool,
+}
+
+impl Plugin for AutopilotPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(AutopilotSettings { enabled: self.enabled })
+            .add_system(sync_autopilot.run_in_state(GameState::Game));
+    }
+}
+
+#[derive(Resource)]
+pub struct AutopilotSettings {
+    /// Whether dogs are steered by the autopilot instead of their players.
+    pub enabled: bool,
+}
+
+/// Replaces the player input of a dog.
+#[derive(Component, Default)]
+pub struct Autopilot {
+    pub mode: AutopilotMode,
+    /// Where the dog is heading, if there is anything left to herd.
+    pub target: Option<Vec2>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum AutopilotMode {
+    /// Fetching the sheep furthest from the flock.
+    #[default]
+    Collecting,
+    /// Pushing the flock toward a goal zone.
+    Driving,
+}
+
+/// Toggles the autopilot for every dog when the hint key is pressed, and hands new dogs to it
+/// while it is enabled.
+fn sync_autopilot(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut settings: ResMut<AutopilotSettings>,
+    query: Query<(Entity, Option<&Autopilot>), With<player::PlayerInput>>,
+    config: Res<Configuration>,
+) {
+    if keyboard_input.any_just_pressed(config.autopilot.toggle.iter().copied()) {
+        settings.enabled = !settings.enabled;
+    }
+    for (entity, autopilot) in query.iter() {
+        match (settings.enabled, autopilot.is_some()) {
+            (true, false) => { commands.entity(entity).insert(Autopilot::default()); }
+            (false, true) => { commands.entity(entity).remove::<Autopilot>(); }
+            _ => {}
+        }
+    }
+}
+
+pub fn steer_dogs(
+    mut dog_query: Query<(&mut Autopilot, &mut Influences, &GlobalTransform, &player::PlayerInput)>,
+    sheep_query: Query<&GlobalTransform, (With<sheep::Flocking>, With<zone::CountsTowardGoal>)>,
+    zone_query: Query<(&GlobalTransform, &zone::GoalZone)>,
+    config: Res<Configuration>,
+) {
+    let config = &config.autopilot;
+    let zones: Vec<(Vec2, Vec2, bool)> = zone_query.iter()
+        .map(|(transform, zone)| (transform.translation().truncate(), zone.size, zone.num_sheep < zone.target))
+        .collect();
+    // Sheep that already made it into a zone are left alone
+    let sheep: Vec<Vec2> = sheep_query.iter()
+        .map(|transform| transform.translation().truncate())
+        .filter(|sheep| !zones.iter().any(|(centre, size, _)| ((*sheep - *centre).abs() - *size / 2.0).max_element() < 0.0))
+        .collect();
+    // Roles are handed out by player slot so the same dog keeps the same role
+    let mut dogs: Vec<_> = dog_query.iter_mut().collect();
+    dogs.sort_by_key(|(_, _, _, player)| player.slot);
+    let plans = plan_herding(&sheep, &zones, dogs.len(), config);
+    for (index, (mut autopilot, mut influences, transform, _)) in dogs.into_iter().enumerate() {
+        let position = transform.translation().truncate();
+        let target = match plans.get(index) {
+            Some(&(mode, target)) => {
+                autopilot.mode = mode;
+                autopilot.target = Some(target);
+                target
+            }
+            None => {
+                autopilot.target = None;
+                continue;
+            }
+        };
+        let to_target = target - position;
+        let distance = to_target.length();
+        if distance < f32::EPSILON { continue; }
+        let mut speed = f32::min(distance / config.arrival_distance.max(1.0), 1.0);
+        // Creep when close to the flock so the sheep are nudged instead of scattered
+        if sheep.iter().any(|sheep| sheep.distance(position) < config.slow_distance) {
+            speed *= config.slow_speed;
+        }
+        influences.set(player::PLAYER_INPUT_INFLUENCE, (to_target / distance * speed).extend(0.0));
+    }
+}
+
+/// Decides between collecting and driving the `flock` for each of `dogs`, and where each dog
+/// should go to do it. `zones` are the centre, size and whether they still need sheep of every
+/// goal zone. Empty once there is nothing left to herd or nowhere left to herd it to.
+fn plan_herding(
+    flock: &[Vec2],
+    zones: &[(Vec2, Vec2, bool)],
+    dogs: usize,
+    config: &AutopilotConfiguration,
+) -> Vec<(AutopilotMode, Vec2)> {
+    if flock.is_empty() || dogs == 0 { return Vec::new(); }
+    let count = flock.len() as f32;
+    let centre = flock.iter().sum::<Vec2>() / count;
+    let goal = zones.iter()
+        .filter(|(_, _, incomplete)| *incomplete)
+        .map(|(position, _, _)| *position)
+        .min_by(|a, b| a.distance_squared(centre).total_cmp(&b.distance_squared(centre)));
+    let goal = match goal {
+        Some(goal) => goal,
+        None => return Vec::new(),
+    };
+    // Strays are the sheep outside the flock radius, furthest first
+    let flock_radius = config.interaction_distance * count.powf(2.0 / 3.0);
+    let mut strays: Vec<(Vec2, f32)> = flock.iter()
+        .map(|sheep| (*sheep, sheep.distance(centre)))
+        .filter(|(_, distance)| *distance > flock_radius)
+        .collect();
+    strays.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    let collect = |stray: Vec2| {
+        let behind = (stray - centre).normalize_or_zero();
+        (AutopilotMode::Collecting, stray + behind * config.collect_distance)
+    };
+    let behind = (centre - goal).normalize_or_zero();
+    let drive = centre + behind * config.interaction_distance * count.sqrt();
+
+    if dogs == 1 {
+        return vec![match strays.first() {
+            Some(&(stray, _)) => collect(stray),
+            None => (AutopilotMode::Driving, drive),
+        }];
+    }
+    // The other dogs each fetch a different stray, and flank the flock on alternating sides once
+    // there are no strays left for them
+    let side = behind.perp() * config.interaction_distance * count.sqrt();
+    let mut plans = vec![(AutopilotMode::Driving, drive)];
+    for index in 1..dogs {
+        plans.push(match strays.get(index - 1) {
+            Some(&(stray, _)) => collect(stray),
+            None => {
+                let offset = side * ((index + 1) / 2) as f32;
+                let flank = if index % 2 == 1 { offset } else { -offset };
+                (AutopilotMode::Driving, drive + flank)
+            }
+        });
+    }
+    plans
+}
+
+pub fn draw_autopilot_targets(
+    query: Query<(&GlobalTransform, &Autopilot)>,
+    mut lines: ResMut<DebugLines>,
+    configuration: Res<Configuration>,
+) {
+    if !configuration.debug_lines.enable { return; }
+    for (transform, autopilot) in query.iter() {
+        if let Some(target) = autopilot.target {
+            let color = match autopilot.mode {
+                AutopilotMode::Collecting => Color::YELLOW,
+                AutopilotMode::Driving => Color::LIME_GREEN,
+            };
+            lines.line_colored(transform.translation() + Vec3::Z, target.extend(transform.translation().z + 1.0), 0.0, color);
+        }
+    }
+}
@@ -2,6 +2,8 @@ use crate::imports::*;
 
 const NAME: &str = "Fence";
 const Z_INDEX: f32 = 10.0;
+pub const HORIZONTAL_CONFIG_SET: &str = "fence_horizontal";
+pub const VERTICAL_CONFIG_SET: &str = "fence_vertical";
 
 #[derive(Default)]
 pub struct FencePlugin;
@@ -43,7 +45,7 @@ fn populate(
     configuration: Res<Configuration>,
 ) {
     populate.populate(|_ctx, data, mut commands| {
-        let (axis, texture_length) = fence_axis_and_length(&configuration.animation, &data.orientation);
+        let (axis, texture_length) = fence_axis_and_length(&configuration, &data.orientation);
         commands.despawn_descendants();
         commands.insert((
             TransformBundle::from_transform(Transform::from_translation(data.position.extend(0.0))),
@@ -56,7 +58,7 @@ fn populate(
             for i in 0..num_sections {
                 let position = i as f32 * axis * texture_length - 4.0 * i as f32 * axis + Vec3::Z * 0.001 * i as f32;
                 commands.spawn(FenceBundle::new(
-                    &configuration.animation,
+                    &configuration,
                     &data.orientation,
                     position,
                 ));
@@ -95,7 +97,7 @@ fn edit(
                 }
             }
         });
-        let (axis, _texture_length) = fence_axis_and_length(&configuration.animation, &data.orientation);
+        let (axis, _texture_length) = fence_axis_and_length(&configuration, &data.orientation);
         if data.section_length < 0.0 {
             data.section_length = 0.0;
         }
@@ -124,10 +126,20 @@ fn edit(
     });
 }
 
-fn fence_axis_and_length(config: &AnimationConfiguration, orientation: &FenceOrientation) -> (Vec3, f32) {
+fn fence_axis_and_length(config: &Configuration, orientation: &FenceOrientation) -> (Vec3, f32) {
+    let texture_size = config.get_sheet(&orientation.config_set_id()).texture_size;
     match orientation {
-        FenceOrientation::Horizontal => (Vec3::X, config.fence_horizontal.texture_size.x * GLOBAL_TEXTURE_SCALE),
-        FenceOrientation::Vertical => (-Vec3::Y, config.fence_vertical.texture_size.y * GLOBAL_TEXTURE_SCALE),
+        FenceOrientation::Horizontal => (Vec3::X, texture_size.x * GLOBAL_TEXTURE_SCALE),
+        FenceOrientation::Vertical => (-Vec3::Y, texture_size.y * GLOBAL_TEXTURE_SCALE),
+    }
+}
+
+impl FenceOrientation {
+    fn config_set_id(&self) -> ConfigurationSetId {
+        match self {
+            FenceOrientation::Horizontal => ConfigurationSetId::new(HORIZONTAL_CONFIG_SET),
+            FenceOrientation::Vertical => ConfigurationSetId::new(VERTICAL_CONFIG_SET),
+        }
     }
 }
 
@@ -141,16 +153,9 @@ pub struct FenceBundle {
 }
 
 impl FenceBundle {
-    pub fn new(config: &AnimationConfiguration, fence_orientation: &FenceOrientation, position: Vec3) -> Self {
-        // let (config_set_id, dimensions)= match fence_orientation {
-        //     FenceOrientation::Horizontal => { (ConfigurationSetId::FenceHorizontal, Vec2::new(5.0, 2.0) )}
-        //     FenceOrientation::Vertical => { (ConfigurationSetId::FenceVertical, Vec2::new(2.0, 5.0)) }
-        // };
-        let config_set_id = match fence_orientation {
-            FenceOrientation::Horizontal => { ConfigurationSetId::FenceHorizontal }
-            FenceOrientation::Vertical => { ConfigurationSetId::FenceVertical }
-        };
-        let config_set = config.get_set(&config_set_id);
+    pub fn new(config: &Configuration, fence_orientation: &FenceOrientation, position: Vec3) -> Self {
+        let config_set_id = fence_orientation.config_set_id();
+        let config_set = config.get_sheet(&config_set_id);
         let dimensions = config_set.texture_size;
         FenceBundle {
             animation_bundle: AnimationBundle::from(config_set, position + Vec3::Z * Z_INDEX),
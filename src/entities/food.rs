@@ -2,6 +2,9 @@ use crate::imports::*;
 
 const NAME: &str = "Food";
 const Z_INDEX: f32 = 18.0;
+pub const CONFIG_SET: &str = "food";
+
+pub const FOOD_INFLUENCE: InfluenceKey = InfluenceKey("food");
 
 #[derive(Default)]
 pub struct FoodPlugin;
@@ -18,6 +21,7 @@ impl Plugin for FoodPlugin {
                     }
                 }))
         });
+        app.register_influence(FOOD_INFLUENCE, 1.0, InfluenceGroup::Normal, Color::PINK);
     }
 }
 
@@ -37,7 +41,7 @@ fn populate(
 ) {
     populate.populate(|_ctx, data, mut commands| {
         commands.insert(FoodBundle::new(
-            &configuration.animation,
+            &configuration,
             data,
         ));
     });
@@ -47,13 +51,16 @@ fn edit(
     mut edit: YoleckEdit<EditorFood>,
 ) {
     edit.edit(|_ctx, data, ui| {
-        ui.add(egui::DragValue::new(&mut data.strength));
+        ui.horizontal(|ui| {
+            ui.add(egui::Label::new("Strength: "));
+            ui.add(egui::DragValue::new(&mut data.strength).speed(0.1));
+        });
     });
 }
 
 #[derive(Component)]
-struct Food {
-    _strength: f32,
+pub struct Food {
+    pub strength: f32,
 }
 
 #[derive(Bundle)]
@@ -67,15 +74,16 @@ pub struct FoodBundle {
 }
 
 impl FoodBundle {
-    fn new(config: &AnimationConfiguration, editor_food: &EditorFood) -> Self {
+    fn new(config: &Configuration, editor_food: &EditorFood) -> Self {
         let dimension = 13.0;
+        let config_set_id = ConfigurationSetId::new(CONFIG_SET);
         FoodBundle {
-            animation_bundle: AnimationBundle::from(&config.food, editor_food.position.extend(Z_INDEX)),
+            animation_bundle: AnimationBundle::from(config.get_sheet(&config_set_id), editor_food.position.extend(Z_INDEX)),
             collider: Collider::cuboid(dimension, dimension),
             rigid_body: RigidBody::Fixed,
             name: Name::new(NAME),
-            config_set_id: ConfigurationSetId::Food,
-            food: Food { _strength: editor_food.strength},
+            config_set_id,
+            food: Food { strength: editor_food.strength },
         }
     }
 }
@@ -83,6 +91,28 @@ impl FoodBundle {
 #[derive(Component)]
 pub struct LikesFood;
 
-// pub fn go_for_food(
-//     mut query: Query<>
-// )
+/// Pulls food-loving entities toward every food source within reach, more strongly the closer
+/// and stronger the food is. Entities that are already at the food are left alone to eat.
+pub fn go_for_food(
+    mut query: Query<(&mut Influences, &GlobalTransform), With<LikesFood>>,
+    food_query: Query<(&GlobalTransform, &Food)>,
+    config: Res<Configuration>,
+) {
+    let config = &config.food;
+    if !config.enabled { return; }
+    for (mut influences, transform) in query.iter_mut() {
+        let position = transform.translation().truncate();
+        let mut attraction = Vec2::ZERO;
+        for (food_transform, food) in food_query.iter() {
+            let to_food = food_transform.translation().truncate() - position;
+            let distance = to_food.length();
+            if distance < config.attraction_distance && distance > config.satisfied_distance {
+                let distance_scale = (config.attraction_distance - distance) / config.attraction_distance;
+                attraction += to_food / distance * food.strength * distance_scale;
+            }
+        }
+        if attraction != Vec2::ZERO {
+            influences.set(FOOD_INFLUENCE, attraction.extend(0.0) * config.scale / 10.0);
+        }
+    }
+}
@@ -24,13 +24,28 @@ pub enum GameRulesCommand {
     CheckSheepWin {
         all_zones_done: bool,
         any_zones_done: bool,
-    }
+    },
+    SheepLost,
 }
 
 #[derive(Component, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct GameRules {
     #[serde(default)]
     pub victory_condition: VictoryCondition,
+    /// The level is failed and restarted when more sheep than this are lost. Unlimited if not set.
+    #[serde(default)]
+    pub max_lost_sheep: Option<usize>,
+    /// How many times the dog may bark in this level. Unlimited if not set.
+    #[serde(default)]
+    pub bark_budget: Option<u32>,
+}
+
+/// Tracks what has happened in the current level. Lives on the same entity as the `GameRules`,
+/// so it is reset whenever a level is loaded.
+#[derive(Component, Default)]
+pub struct LevelProgress {
+    pub lost_sheep: usize,
+    pub barks_used: u32,
 }
 
 #[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize, Default)]
@@ -46,6 +61,7 @@ fn populate(
     populate.populate(|_ctx, data, mut commands| {
         commands.insert((
             data.clone(),
+            LevelProgress::default(),
         ));
     });
 }
@@ -68,21 +84,42 @@ fn edit(
                 }
             }
         });
+        ui.horizontal(|ui| {
+            let mut limit_lost_sheep = data.max_lost_sheep.is_some();
+            ui.checkbox(&mut limit_lost_sheep, "Max lost sheep: ");
+            match (limit_lost_sheep, data.max_lost_sheep.as_mut()) {
+                (true, Some(max_lost_sheep)) => {
+                    ui.add(egui::DragValue::new(max_lost_sheep));
+                }
+                (true, None) => data.max_lost_sheep = Some(0),
+                (false, _) => data.max_lost_sheep = None,
+            }
+        });
+        ui.horizontal(|ui| {
+            let mut limit_barks = data.bark_budget.is_some();
+            ui.checkbox(&mut limit_barks, "Bark budget: ");
+            match (limit_barks, data.bark_budget.as_mut()) {
+                (true, Some(bark_budget)) => {
+                    ui.add(egui::DragValue::new(bark_budget));
+                }
+                (true, None) => data.bark_budget = Some(5),
+                (false, _) => data.bark_budget = None,
+            }
+        });
     });
 }
 
 pub fn handle_game_rules(
     mut event_reader: EventReader<GameRulesCommand>,
-    game_rules_query: Query<&game_rules::GameRules>,
+    mut game_rules_query: Query<(&game_rules::GameRules, &mut LevelProgress)>,
     mut event_writer: EventWriter<LevelEvent>,
 ) {
-    let victory_conditions = if let Ok(game_rules) = game_rules_query.get_single() {
-        &game_rules.victory_condition
-    } else {
-        &VictoryCondition::AllGoalZones
-    };
+    let mut game_rules = game_rules_query.get_single_mut().ok();
+    let victory_conditions = game_rules.as_ref()
+        .map(|(game_rules, _)| game_rules.victory_condition.clone())
+        .unwrap_or_default();
+    let victory_conditions = &victory_conditions;
     for command in event_reader.iter() {
-        println!("{:?}", command);
         match command {
             GameRulesCommand::CheckSheepWin { all_zones_done, any_zones_done } => {
                 match victory_conditions {
@@ -98,6 +135,17 @@ pub fn handle_game_rules(
                     }
                 }
             }
+            GameRulesCommand::SheepLost => {
+                if let Some((game_rules, progress)) = game_rules.as_mut() {
+                    progress.lost_sheep += 1;
+                    if let Some(max_lost_sheep) = game_rules.max_lost_sheep {
+                        // Only when the limit is first crossed, later losses happen while restarting
+                        if progress.lost_sheep == max_lost_sheep + 1 {
+                            event_writer.send(LevelEvent::RestartLevel);
+                        }
+                    }
+                }
+            }
         }
     }
 }
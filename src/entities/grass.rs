@@ -1,3 +1,5 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use crate::imports::*;
 
 #[derive(Default)]
@@ -5,6 +7,14 @@ pub struct GrassPlugin;
 
 const NAME: &str = "Grass";
 const Z_INDEX: f32 = 5.0;
+pub const CONFIG_SET: &str = "grass";
+
+/// Grass shows this animation of its sheet while a gust bends it.
+pub const BENT_ANIMATION: &str = "bent";
+/// Shown once grass has been eaten below `GrassConfiguration::grazed_below`.
+pub const GRAZED_ANIMATION: &str = "grazed";
+/// Shown once grass has been eaten below `GrassConfiguration::bare_below`.
+pub const BARE_ANIMATION: &str = "bare";
 
 impl Plugin for GrassPlugin {
     fn build(&self, app: &mut App) {
@@ -13,22 +23,122 @@ impl Plugin for GrassPlugin {
                 .populate_with(populate)
                 .edit_with(edit)
         });
+        app.add_system(update_grass_sprites.run_in_state(GameState::Game).after("animation"));
     }
 }
 
+#[derive(Component)]
+pub struct Grass {
+    /// How much is left to eat, from 0.0 for bare ground to 1.0 when fully grown.
+    pub amount: f32,
+}
+
 #[derive(Bundle)]
 pub struct GrassBundle {
+    grass: Grass,
     animation_bundle: AnimationBundle,
     name: Name,
     config_set_id: ConfigurationSetId,
 }
 
 impl GrassBundle {
-    pub fn new(config_set: &AnimationSheet, position: Vec2) -> Self {
+    pub fn new(config: &Configuration, position: Vec2) -> Self {
+        let config_set_id = ConfigurationSetId::new(CONFIG_SET);
         GrassBundle {
-            animation_bundle: AnimationBundle::from(config_set, position.extend(Z_INDEX)),
+            grass: Grass { amount: 1.0 },
+            animation_bundle: AnimationBundle::from(config.get_sheet(&config_set_id), position.extend(Z_INDEX)),
             name: Name::new(NAME),
-            config_set_id: ConfigurationSetId::Grass,
+            config_set_id,
+        }
+    }
+}
+
+/// Sheep that stand still to graze eat the grass under them, and all grass slowly grows back.
+pub fn graze_grass(
+    mut grazer_query: Query<(&GlobalTransform, &sheep::Grazing, Option<&mut needs::Needs>)>,
+    mut grass_query: Query<&mut Grass>,
+    grass_index: Res<GrassIndex>,
+    config: Res<Configuration>,
+    mut neighbours: Local<Vec<usize>>,
+) {
+    let nourishment = if config.needs.enabled { config.needs.grass_nourishment } else { 0.0 };
+    let config = &config.grass;
+    if !config.enabled { return; }
+    for mut grass in grass_query.iter_mut() {
+        grass.amount = f32::min(grass.amount + config.regrow_rate * TIME_STEP, 1.0);
+    }
+    for (transform, grazing, needs) in grazer_query.iter_mut() {
+        if grazing.current_direction.is_some() { continue; }
+        let position = transform.translation();
+        grass_index.query_radius(position, config.eat_radius, &mut neighbours);
+        let nearest = neighbours.iter()
+            .map(|&index| &grass_index.entries()[index])
+            .filter(|entry| grass_query.get(entry.entity).map_or(false, |grass| grass.amount > 0.0))
+            .min_by(|a, b| {
+                a.position.distance_squared(position).total_cmp(&b.position.distance_squared(position))
+            });
+        if let Some(entry) = nearest {
+            if let Ok(mut grass) = grass_query.get_mut(entry.entity) {
+                let eaten = f32::min(config.eat_rate * TIME_STEP, grass.amount);
+                grass.amount -= eaten;
+                if let Some(mut needs) = needs {
+                    needs.eat(eaten * nourishment);
+                }
+            }
+        }
+    }
+}
+
+/// Direction from `position` toward the grass most worth walking to within the seek distance,
+/// favouring grass that is fuller and closer. Grass already within reach is skipped.
+pub fn direction_to_grass(
+    position: Vec3,
+    grass_index: &GrassIndex,
+    grass_query: &Query<&Grass>,
+    config: &GrassConfiguration,
+    neighbours: &mut Vec<usize>,
+) -> Option<Vec3> {
+    grass_index.query_radius(position, config.seek_distance, neighbours);
+    let mut best: Option<(f32, Vec3)> = None;
+    for &index in neighbours.iter() {
+        let entry = &grass_index.entries()[index];
+        let amount = match grass_query.get(entry.entity) {
+            Ok(grass) => grass.amount,
+            Err(_) => continue,
+        };
+        let to_grass = (entry.position - position).truncate().extend(0.0);
+        let distance = to_grass.length();
+        if distance < config.eat_radius || amount <= 0.0 { continue; }
+        let score = amount * (1.0 - distance / config.seek_distance);
+        if best.map_or(true, |(best_score, _)| score > best_score) {
+            best = Some((score, to_grass / distance));
+        }
+    }
+    best.map(|(_, direction)| direction)
+}
+
+fn update_grass_sprites(
+    mut query: Query<(&mut TextureAtlasSprite, &GlobalTransform, &ConfigurationSetId, &Grass)>,
+    wind: Res<wind::Wind>,
+    config: Res<Configuration>,
+) {
+    for (mut sprite, transform, config_id, grass) in query.iter_mut() {
+        let sheet = config.get_sheet(config_id);
+        let animation: &str = if grass.amount < config.grass.bare_below {
+            BARE_ANIMATION
+        } else if grass.amount < config.grass.grazed_below {
+            GRAZED_ANIMATION
+        } else if wind.sway_at(&config.wind, transform.translation().truncate()) > config.wind.sway_threshold {
+            BENT_ANIMATION
+        } else {
+            &sheet.initial_animation
+        };
+        let index = match sheet.animations.get(animation) {
+            Some(animation) => animation.first_index,
+            None => continue,
+        };
+        if sprite.index != index {
+            sprite.index = index;
         }
     }
 }
@@ -47,16 +157,17 @@ fn default_spawned() -> usize { 300 }
 
 fn default_extents() -> Vec2 { Vec2::new(1000.0, 1000.0) }
 
+/// Grass without stored positions is always generated the same way, independent of the simulation seed.
 fn default_entities() -> Vec<Vec2> {
-    generate_positions(default_spawned(), default_extents())
+    generate_positions(default_spawned(), default_extents(), &mut StdRng::seed_from_u64(0))
 }
-fn generate_positions(num: usize, extents: Vec2) -> Vec<Vec2> {
+fn generate_positions(num: usize, extents: Vec2, rng: &mut impl Rng) -> Vec<Vec2> {
     let distribution_x = Uniform::new(-extents.x, extents.x);
     let distribution_y = Uniform::new(-extents.y, extents.y);
     let mut positions = Vec::new();
     for _ in 0..num {
-        let x = distribution_x.sample(&mut rand::thread_rng());
-        let y = distribution_y.sample(&mut rand::thread_rng());
+        let x = distribution_x.sample(rng);
+        let y = distribution_y.sample(rng);
         positions.push(Vec2::new(x, y));
     }
     positions
@@ -76,7 +187,7 @@ fn populate(
         commands.with_children(|commands| {
             for position in data.positions.iter() {
                 commands.spawn(GrassBundle::new(
-                    &configuration.animation.grass,
+                    &configuration,
                     *position,
                 ));
             }
@@ -84,7 +195,7 @@ fn populate(
     });
 }
 
-fn edit(mut edit: YoleckEdit<EditorGrass>) {
+fn edit(mut edit: YoleckEdit<EditorGrass>, mut rng: ResMut<SimulationRng>) {
     edit.edit(|_ctx, data, ui| {
         ui.horizontal(|ui| {
             ui.add(egui::Label::new("Num grass instances"));
@@ -98,7 +209,7 @@ fn edit(mut edit: YoleckEdit<EditorGrass>) {
             ui.add(egui::DragValue::new(&mut data.extents.y));
         });
         if ui.add(egui::Button::new("Regenerate")).clicked() {
-            data.positions = generate_positions(data.num_positions, data.extents);
+            data.positions = generate_positions(data.num_positions, data.extents, &mut rng.0);
         }
         ui.collapsing("Individual grass elements", |ui| {
             egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
@@ -0,0 +1,245 @@
+use std::collections::BTreeMap;
+use bevy::utils::HashMap;
+use crate::imports::*;
+
+const NAME: &str = "LevelSettings";
+
+#[derive(Default)]
+pub struct LevelSettingsPlugin;
+
+impl Plugin for LevelSettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_yoleck_handler({
+            YoleckTypeHandler::<LevelSettings>::new(NAME)
+                .populate_with(populate)
+                .edit_with(edit)
+        });
+        app.init_resource::<OverriddenConfiguration>();
+        app.add_system(apply_level_settings);
+    }
+}
+
+/// Configuration values that only apply while this level is loaded. Anything left unset uses the
+/// global configuration.
+#[derive(Component, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub struct LevelSettings {
+    /// Max speed per configuration set name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub max_speed: BTreeMap<String, f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flocking: Option<FlockingConfiguration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runner: Option<RunnerConfiguration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grazing_scale: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zoom: Option<f32>,
+}
+
+/// The global values that the current level settings replaced, so they can be put back.
+#[derive(Resource, Default)]
+pub struct OverriddenConfiguration {
+    backup: Option<ConfigurationBackup>,
+    /// The settings that are applied, to apply them again after the global values changed.
+    settings: Option<LevelSettings>,
+}
+
+struct ConfigurationBackup {
+    max_speed: HashMap<String, f32>,
+    flocking: FlockingConfiguration,
+    runner: RunnerConfiguration,
+    grazing_scale: f32,
+    zoom: f32,
+}
+
+impl OverriddenConfiguration {
+    fn apply(&mut self, settings: &LevelSettings, config: &mut Configuration) {
+        self.restore(config);
+        self.settings = Some(settings.clone());
+        self.backup = Some(ConfigurationBackup {
+            max_speed: config.sets.iter().map(|(name, set)| (name.clone(), set.max_speed)).collect(),
+            flocking: config.flocking.clone(),
+            runner: config.runner.clone(),
+            grazing_scale: config.grazing_scale,
+            zoom: config.zoom,
+        });
+        for (name, max_speed) in settings.max_speed.iter() {
+            match config.sets.get_mut(name) {
+                Some(set) => set.max_speed = *max_speed,
+                None => warn!("Level settings override the max speed of unknown configuration set \"{}\"", name),
+            }
+        }
+        if let Some(flocking) = &settings.flocking {
+            config.flocking = flocking.clone();
+        }
+        if let Some(runner) = &settings.runner {
+            config.runner = runner.clone();
+        }
+        if let Some(grazing_scale) = settings.grazing_scale {
+            config.grazing_scale = grazing_scale;
+        }
+        if let Some(zoom) = settings.zoom {
+            config.zoom = zoom;
+        }
+    }
+
+    /// Puts back the global values, for example when the level is unloaded.
+    pub fn restore(&mut self, config: &mut Configuration) {
+        self.settings = None;
+        if let Some(backup) = self.backup.take() {
+            for (name, max_speed) in backup.max_speed {
+                if let Some(set) = config.sets.get_mut(&name) {
+                    set.max_speed = max_speed;
+                }
+            }
+            config.flocking = backup.flocking;
+            config.runner = backup.runner;
+            config.grazing_scale = backup.grazing_scale;
+            config.zoom = backup.zoom;
+        }
+    }
+
+    /// Runs `change` on the global values without the level overrides, then applies the overrides
+    /// on top again. Presets are saved and loaded through this so they never contain the values
+    /// of a level, and so a preset loaded mid-level is not undone when the level ends.
+    pub fn without_overrides<R>(&mut self, config: &mut Configuration, change: impl FnOnce(&mut Configuration) -> R) -> R {
+        let settings = self.settings.take();
+        self.restore(config);
+        let result = change(config);
+        if let Some(settings) = settings {
+            self.apply(&settings, config);
+        }
+        result
+    }
+}
+
+fn populate(mut populate: YoleckPopulate<LevelSettings>) {
+    populate.populate(|_ctx, data, mut commands| {
+        commands.insert(data.clone());
+    });
+}
+
+/// Overrides are applied when the settings entity is spawned or edited, and reverted once it is
+/// gone. Level changes also revert them straight away, see `levels::handle_level_events`.
+fn apply_level_settings(
+    changed_query: Query<&LevelSettings, Changed<LevelSettings>>,
+    settings_query: Query<(), With<LevelSettings>>,
+    mut overridden: ResMut<OverriddenConfiguration>,
+    mut config: ResMut<Configuration>,
+) {
+    // Checked every frame instead of through removal detection, which misses despawns that are
+    // applied after this system has already run in that frame
+    if settings_query.is_empty() {
+        overridden.restore(&mut config);
+    }
+    for settings in changed_query.iter() {
+        overridden.apply(settings, &mut config);
+    }
+}
+
+fn edit(mut edit: YoleckEdit<LevelSettings>, config: Res<Configuration>) {
+    edit.edit(|_ctx, data, ui| {
+        let mut removed_max_speed = None;
+        for (name, max_speed) in data.max_speed.iter_mut() {
+            ui.horizontal(|ui| {
+                ui.add(egui::Label::new(format!("Max speed of {}: ", name)));
+                ui.add(egui::DragValue::new(max_speed).clamp_range(0.0..=f32::MAX));
+                if ui.button("Remove").clicked() {
+                    removed_max_speed = Some(name.clone());
+                }
+            });
+        }
+        if let Some(name) = removed_max_speed {
+            data.max_speed.remove(&name);
+        }
+        if let Some(zoom) = data.zoom.as_mut() {
+            ui.horizontal(|ui| {
+                ui.add(egui::Label::new("Zoom: "));
+                ui.add(egui::DragValue::new(zoom).speed(0.01).clamp_range(0.1..=10.0));
+                if ui.button("Remove").clicked() {
+                    data.zoom = None;
+                }
+            });
+        }
+        if let Some(grazing_scale) = data.grazing_scale.as_mut() {
+            ui.horizontal(|ui| {
+                ui.add(egui::Label::new("Grazing scale: "));
+                ui.add(egui::DragValue::new(grazing_scale).speed(0.01).clamp_range(0.0..=f32::MAX));
+                if ui.button("Remove").clicked() {
+                    data.grazing_scale = None;
+                }
+            });
+        }
+        if let Some(runner) = data.runner.as_mut() {
+            let mut remove = false;
+            ui.collapsing("Runner", |ui| {
+                drag_value(ui, "Scale: ", &mut runner.scale);
+                drag_value(ui, "Speed fraction: ", &mut runner.speed_fraction);
+                drag_value(ui, "Scare distance: ", &mut runner.scare_distance);
+                drag_value(ui, "Calm speed fraction: ", &mut runner.calm_speed_fraction);
+                drag_value(ui, "Calm scare fraction: ", &mut runner.calm_scare_fraction);
+                remove = ui.button("Remove").clicked();
+            });
+            if remove {
+                data.runner = None;
+            }
+        }
+        if let Some(flocking) = data.flocking.as_mut() {
+            let mut remove = false;
+            ui.collapsing("Flocking", |ui| {
+                ui.checkbox(&mut flocking.alignment_enabled, "Alignment");
+                drag_value(ui, "Alignment distance: ", &mut flocking.alignment_distance);
+                drag_value(ui, "Alignment scale: ", &mut flocking.alignment_scale);
+                drag_value(ui, "Alignment distance cap fraction: ", &mut flocking.alignment_distance_cap_fraction);
+                ui.checkbox(&mut flocking.cohesion_enabled, "Cohesion");
+                ui.checkbox(&mut flocking.cohesion_velocity_scale, "Cohesion velocity scale");
+                drag_value(ui, "Cohesion distance: ", &mut flocking.cohesion_distance);
+                drag_value(ui, "Cohesion scale: ", &mut flocking.cohesion_scale);
+                ui.checkbox(&mut flocking.separation_enabled, "Separation");
+                drag_value(ui, "Separation distance: ", &mut flocking.separation_distance);
+                drag_value(ui, "Separation scale: ", &mut flocking.separation_scale);
+                ui.checkbox(&mut flocking.avoidance_enabled, "Avoidance");
+                drag_value(ui, "Avoidance distance: ", &mut flocking.avoidance_distance);
+                drag_value(ui, "Avoidance scale: ", &mut flocking.avoidance_scale);
+                remove = ui.button("Remove").clicked();
+            });
+            if remove {
+                data.flocking = None;
+            }
+        }
+
+        // New overrides start from the current global value
+        ui.separator();
+        ui.horizontal_wrapped(|ui| {
+            ui.add(egui::Label::new("Override: "));
+            let mut set_names: Vec<&String> = config.sets.keys()
+                .filter(|name| !data.max_speed.contains_key(*name))
+                .collect();
+            set_names.sort();
+            for name in set_names {
+                if ui.button(format!("Max speed of {}", name)).clicked() {
+                    data.max_speed.insert(name.clone(), config.sets[name].max_speed);
+                }
+            }
+            if data.zoom.is_none() && ui.button("Zoom").clicked() {
+                data.zoom = Some(config.zoom);
+            }
+            if data.grazing_scale.is_none() && ui.button("Grazing scale").clicked() {
+                data.grazing_scale = Some(config.grazing_scale);
+            }
+            if data.runner.is_none() && ui.button("Runner").clicked() {
+                data.runner = Some(config.runner.clone());
+            }
+            if data.flocking.is_none() && ui.button("Flocking").clicked() {
+                data.flocking = Some(config.flocking.clone());
+            }
+        });
+    });
+}
+
+fn drag_value(ui: &mut egui::Ui, label: &str, value: &mut f32) {
+    ui.horizontal(|ui| {
+        ui.add(egui::Label::new(label));
+        ui.add(egui::DragValue::new(value).clamp_range(0.0..=f32::MAX));
+    });
+}
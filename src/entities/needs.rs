@@ -0,0 +1,82 @@
+use crate::imports::*;
+
+/// Internal state of an animal, from 0.0 to 1.0.
+#[derive(Component)]
+pub struct Needs {
+    /// Rises over time, satisfied by eating.
+    pub hunger: f32,
+    /// Drains while running from threats, recovers while standing still.
+    pub energy: f32,
+    /// Whether the animal ate during the last tick.
+    pub eating: bool,
+}
+
+impl Default for Needs {
+    fn default() -> Self {
+        Needs {
+            hunger: 0.0,
+            energy: 1.0,
+            eating: false,
+        }
+    }
+}
+
+impl Needs {
+    /// How much of a scare a hungry animal takes notice of while it is eating.
+    pub fn flee_factor(&self, config: &NeedsConfiguration) -> f32 {
+        if !config.enabled || !self.eating { return 1.0; }
+        1.0 - self.hunger * config.hunger_resistance
+    }
+
+    pub fn eat(&mut self, nourishment: f32) {
+        self.hunger = f32::max(self.hunger - nourishment, 0.0);
+        self.eating = true;
+    }
+}
+
+pub fn update_needs(
+    mut query: Query<(
+        &mut Needs,
+        &mut SpeedModifier,
+        &GlobalTransform,
+        &Velocity,
+        &ConfigurationSetId,
+        Option<&sheep::Runner>,
+    )>,
+    food_query: Query<&GlobalTransform, With<food::Food>>,
+    config: Res<Configuration>,
+) {
+    if !config.needs.enabled { return; }
+    for (
+        mut needs,
+        mut speed_modifier,
+        transform,
+        velocity,
+        config_id,
+        runner,
+    ) in query.iter_mut() {
+        let position = transform.translation();
+        needs.eating = false;
+        needs.hunger = f32::min(needs.hunger + config.needs.hunger_rate * TIME_STEP, 1.0);
+        let at_food = food_query.iter().any(|food_transform| {
+            food_transform.translation().distance(position) < config.food.satisfied_distance
+        });
+        if at_food {
+            needs.eat(config.needs.food_nourishment * TIME_STEP);
+        }
+
+        let running = runner.map_or(0.0, |runner| runner.magnitude.max(0.0));
+        let speed_fraction = velocity.linvel.length() / config.get_set(config_id).max_speed;
+        if running > 0.0 {
+            needs.energy -= config.needs.energy_drain * running * TIME_STEP;
+        } else if speed_fraction < config.needs.rest_speed_fraction {
+            needs.energy += config.needs.energy_recovery * TIME_STEP;
+        }
+        needs.energy = needs.energy.clamp(0.0, 1.0);
+
+        if needs.energy < config.needs.exhausted_below {
+            let tiredness = 1.0 - needs.energy / config.needs.exhausted_below;
+            speed_modifier.multiplier *= 1.0 - tiredness * (1.0 - config.needs.exhausted_speed);
+        }
+    }
+}
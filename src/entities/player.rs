@@ -2,6 +2,9 @@ use crate::imports::*;
 
 const NAME: &str = "Player";
 const Z_INDEX: f32 = 50.0;
+pub const CONFIG_SET: &str = "player";
+
+pub const PLAYER_INPUT_INFLUENCE: InfluenceKey = InfluenceKey("player_input");
 
 #[derive(Default)]
 pub struct PlayerPlugin;
@@ -11,13 +14,17 @@ impl Plugin for PlayerPlugin {
         app.add_yoleck_handler({
             YoleckTypeHandler::<EditorPlayer>::new(NAME)
                 .populate_with(populate)
+                .edit_with(edit)
                 .with(yoleck_vpeol_position_edit_adapter(|data: &mut EditorPlayer| {
                     YoleckVpeolTransform2dProjection {
                         translation: &mut data.position,
                     }
                 }))
         });
-        app.add_system(apply_player_input);
+        app.register_influence(PLAYER_INPUT_INFLUENCE, 1.0, InfluenceGroup::Normal, Color::WHITE);
+        app.add_event::<BarkEvent>();
+        app.add_system(read_bark_input.run_in_state(GameState::Game));
+        app.add_system(read_mouse_input.run_in_state(GameState::Game));
     }
 }
 
@@ -25,11 +32,34 @@ impl Plugin for PlayerPlugin {
 struct EditorPlayer {
     #[serde(default)]
     position: Vec2,
+    /// Which entry of the player bindings controls this dog.
+    #[serde(default)]
+    slot: usize,
+    /// Configuration set to use instead of [`CONFIG_SET`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    config_set: Option<String>,
 }
 
 fn populate(mut populate: YoleckPopulate<EditorPlayer>, configuration: Res<Configuration>) {
     populate.populate(|_ctx, data, mut commands| {
-        commands.insert(PlayerBundle::new(&configuration.animation.player, data.position));
+        let config_set_id = match configuration.set_id_or(data.config_set.as_deref(), CONFIG_SET) {
+            Ok(config_set_id) => config_set_id,
+            Err(error) => {
+                error!("Player not spawned: {}", error);
+                return;
+            }
+        };
+        commands.insert(PlayerBundle::new(&configuration, config_set_id, data.position, data.slot));
+    });
+}
+
+fn edit(mut edit: YoleckEdit<EditorPlayer>, configuration: Res<Configuration>) {
+    edit.edit(|_ctx, data, ui| {
+        ui.horizontal(|ui| {
+            ui.add(egui::Label::new("Player slot: "));
+            ui.add(egui::DragValue::new(&mut data.slot));
+        });
+        configuration_set_ui(ui, &mut data.config_set, &configuration, CONFIG_SET);
     });
 }
 
@@ -38,47 +68,269 @@ fn populate(mut populate: YoleckPopulate<EditorPlayer>, configuration: Res<Confi
 pub struct PlayerBundle {
     actor: Actor,
     player: PlayerInput,
+    barker: Barker,
+    stamina: Stamina,
+    mouse_target: MouseTarget,
     name: Name,
     config_set_id: ConfigurationSetId,
 }
 
 impl PlayerBundle {
-    pub fn new(config_set: &AnimationSheet, position: Vec2) -> Self {
+    pub fn new(config: &Configuration, config_set_id: ConfigurationSetId, position: Vec2, slot: usize) -> Self {
+        let mut actor = Actor::new(
+            config.get_sheet(&config_set_id),
+            position.extend(Z_INDEX),
+            Collider::ball(config.get_set(&config_set_id).collider_radius),
+        );
+        if let Some(bindings) = config.players.get(slot) {
+            actor.animation_bundle.sprite_sheet.sprite.color = bindings.tint;
+        }
         PlayerBundle {
-            actor: Actor::new(config_set, position.extend(Z_INDEX), Collider::ball(15.0)),
-            player: PlayerInput {},
+            actor,
+            player: PlayerInput { slot },
+            barker: Barker::default(),
+            stamina: Stamina::default(),
+            mouse_target: MouseTarget::default(),
             name: Name::new(NAME),
-            config_set_id: ConfigurationSetId::Player,
+            config_set_id,
         }
     }
 }
 
 #[derive(Component)]
-pub struct PlayerInput {}
+pub struct PlayerInput {
+    /// Index into `Configuration::players`. Slots without bindings receive no input.
+    pub slot: usize,
+}
 
+/// Keys and the d-pad give a full speed direction, while the left stick gives a speed proportional
+/// to how far it is pushed, so the dog can creep up on the flock.
 pub fn apply_player_input(
     keyboard_input: Res<Input<KeyCode>>,
-    mut query: Query<&mut Influences, With<PlayerInput>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut query: Query<(&mut Influences, &PlayerInput, &GlobalTransform, &MouseTarget), Without<autopilot::Autopilot>>,
+    config: Res<Configuration>,
+) {
+    for (mut influences, player, transform, mouse_target) in query.iter_mut() {
+        let bindings = match config.players.get(player.slot) {
+            Some(bindings) => bindings,
+            None => continue,
+        };
+        if bindings.scheme == ControlScheme::Mouse {
+            influences.set(
+                PLAYER_INPUT_INFLUENCE,
+                mouse_target.steer(transform.translation().truncate(), &config.mouse).extend(0.0),
+            );
+            continue;
+        }
+        let gamepad_pressed = |button_type| bindings.gamepad.map_or(false, |id| {
+            gamepad_buttons.pressed(GamepadButton::new(Gamepad::new(id), button_type))
+        });
+        let pressed = |keys: &Vec<KeyCode>, button_type| {
+            keyboard_input.any_pressed(keys.iter().copied()) || gamepad_pressed(button_type)
+        };
+        let mut direction = Vec3::new(0.0, 0.0, 0.0);
+        if pressed(&bindings.left, GamepadButtonType::DPadLeft) {
+            direction.x -= 1.0;
+        }
+        if pressed(&bindings.right, GamepadButtonType::DPadRight) {
+            direction.x += 1.0;
+        }
+        if pressed(&bindings.up, GamepadButtonType::DPadUp) {
+            direction.y += 1.0;
+        }
+        if pressed(&bindings.down, GamepadButtonType::DPadDown) {
+            direction.y -= 1.0;
+        }
+        let stick = bindings.gamepad.map_or(Vec2::ZERO, |id| {
+            read_stick(&gamepad_axes, Gamepad::new(id), config.gamepad.dead_zone)
+        });
+        let input = direction.normalize_or_zero() + stick.extend(0.0);
+        influences.set(PLAYER_INPUT_INFLUENCE, input.clamp_length_max(1.0));
+    }
+}
+
+/// Reads the left stick of `gamepad` with a radial dead zone. The remaining range is rescaled so
+/// that the input starts from zero at the edge of the dead zone.
+pub fn read_stick(gamepad_axes: &Axis<GamepadAxis>, gamepad: Gamepad, dead_zone: f32) -> Vec2 {
+    let axis = |axis_type| gamepad_axes.get(GamepadAxis::new(gamepad, axis_type)).unwrap_or(0.0);
+    let stick = Vec2::new(axis(GamepadAxisType::LeftStickX), axis(GamepadAxisType::LeftStickY));
+    let length = stick.length();
+    if length <= dead_zone || dead_zone >= 1.0 {
+        return Vec2::ZERO;
+    }
+    let magnitude = f32::min((length - dead_zone) / (1.0 - dead_zone), 1.0);
+    stick / length * magnitude
+}
+
+#[derive(Component)]
+pub struct Stamina {
+    /// From 0.0 when exhausted to 1.0 when fully rested.
+    pub current: f32,
+    pub sprinting: bool,
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Stamina {
+            current: 1.0,
+            sprinting: false,
+        }
+    }
+}
+
+/// Sprinting lifts the dog above its normal max speed for as long as its stamina lasts. Stamina
+/// comes back whenever the dog is not sprinting, and an exhausted dog has to get some of it back
+/// before it can start sprinting again.
+pub fn update_stamina(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut query: Query<(&mut Stamina, &mut SpeedModifier, &PlayerInput, &Influences)>,
+    config: Res<Configuration>,
 ) {
-    let mut direction = Vec3::new(0.0, 0.0, 0.0);
-    if keyboard_input.pressed(KeyCode::Left) ||
-        keyboard_input.pressed(KeyCode::A) {
-        direction.x -= 1.0;
+    for (mut stamina, mut speed_modifier, player, influences) in query.iter_mut() {
+        let bindings = match config.players.get(player.slot) {
+            Some(bindings) => bindings,
+            None => continue,
+        };
+        let held = keyboard_input.any_pressed(bindings.sprint.iter().copied())
+            || bindings.gamepad.map_or(false, |id| {
+                gamepad_buttons.pressed(GamepadButton::new(Gamepad::new(id), config.gamepad.buttons.sprint))
+            });
+        let moving = influences.get(PLAYER_INPUT_INFLUENCE).map_or(false, |input| input.length() > 0.0);
+        let can_sprint = stamina.current > 0.0
+            && (stamina.sprinting || stamina.current >= config.stamina.min_to_sprint);
+        stamina.sprinting = config.stamina.enabled && held && moving && can_sprint;
+        if stamina.sprinting {
+            stamina.current = f32::max(stamina.current - config.stamina.drain_rate * TIME_STEP, 0.0);
+            speed_modifier.multiplier *= config.stamina.sprint_speed;
+        } else {
+            stamina.current = f32::min(stamina.current + config.stamina.regen_rate * TIME_STEP, 1.0);
+        }
     }
-    if keyboard_input.pressed(KeyCode::Right) ||
-        keyboard_input.pressed(KeyCode::D) {
-        direction.x += 1.0;
+}
+
+/// Where a mouse controlled dog is heading, updated every frame from the cursor.
+#[derive(Component, Default)]
+pub struct MouseTarget {
+    pub target: Option<Vec2>,
+    /// Toggled with the right mouse button. While holding, the dog stays where it is.
+    pub holding: bool,
+}
+
+impl MouseTarget {
+    /// Steers toward the target, slowing down within the arrival distance.
+    pub fn steer(&self, position: Vec2, config: &MouseConfiguration) -> Vec2 {
+        let target = match self.target {
+            Some(target) if !self.holding => target,
+            _ => return Vec2::ZERO,
+        };
+        let to_target = target - position;
+        let distance = to_target.length();
+        if distance < config.stop_distance {
+            return Vec2::ZERO;
+        }
+        let speed = if config.arrival_distance > 0.0 {
+            f32::min(distance / config.arrival_distance, 1.0)
+        } else {
+            1.0
+        };
+        to_target / distance * speed
     }
-    if keyboard_input.pressed(KeyCode::Up) ||
-        keyboard_input.pressed(KeyCode::W) {
-        direction.y += 1.0;
+}
+
+pub fn read_mouse_input(
+    windows: Option<Res<Windows>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    camera_query: Query<(&GlobalTransform, &OrthographicProjection), With<Camera>>,
+    mut query: Query<(&mut MouseTarget, &PlayerInput)>,
+    config: Res<Configuration>,
+) {
+    let cursor = windows.as_ref()
+        .and_then(|windows| windows.get_primary())
+        .and_then(|window| {
+            let cursor = window.cursor_position()?;
+            Some(cursor - Vec2::new(window.width(), window.height()) / 2.0)
+        });
+    let world_cursor = match (cursor, camera_query.get_single()) {
+        (Some(cursor), Ok((camera_transform, projection))) => {
+            Some(camera_transform.transform_point((cursor * projection.scale).extend(0.0)).truncate())
+        }
+        _ => None,
+    };
+    let toggle_hold = mouse_buttons.just_pressed(MouseButton::Right);
+    for (mut mouse_target, player) in query.iter_mut() {
+        let uses_mouse = config.players.get(player.slot)
+            .map_or(false, |bindings| bindings.scheme == ControlScheme::Mouse);
+        if !uses_mouse { continue; }
+        if toggle_hold {
+            mouse_target.holding = !mouse_target.holding;
+        }
+        // Keep heading for the last known position when the cursor leaves the window
+        if world_cursor.is_some() {
+            mouse_target.target = world_cursor;
+        }
     }
-    if keyboard_input.pressed(KeyCode::Down) ||
-        keyboard_input.pressed(KeyCode::S) {
-        direction.y -= 1.0;
+}
+
+#[derive(Component, Default)]
+pub struct Barker {
+    /// Set when the bark button is pressed, consumed by the next simulation tick.
+    pub requested: bool,
+    /// True only during the tick in which the bark happened.
+    pub barked: bool,
+    pub cooldown_left: f32,
+}
+
+/// Sent by [`bark`] whenever a dog barks.
+pub struct BarkEvent {
+    pub barker: Entity,
+}
+
+/// Input is read every frame so that presses are not lost between fixed simulation ticks.
+pub fn read_bark_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut query: Query<(&mut Barker, &PlayerInput)>,
+    config: Res<Configuration>,
+) {
+    for (mut barker, player) in query.iter_mut() {
+        let bindings = match config.players.get(player.slot) {
+            Some(bindings) => bindings,
+            None => continue,
+        };
+        let pressed = keyboard_input.any_just_pressed(bindings.bark.iter().copied())
+            || bindings.gamepad.map_or(false, |id| {
+                gamepad_buttons.just_pressed(GamepadButton::new(Gamepad::new(id), config.gamepad.buttons.bark))
+            });
+        if pressed {
+            barker.requested = true;
+        }
     }
-    direction = direction.normalize_or_zero();
-    for mut influences in query.iter_mut() {
-        influences.player_input_influence = Some(direction);
+}
+
+pub fn bark(
+    mut query: Query<(Entity, &mut Barker)>,
+    mut bark_events: EventWriter<BarkEvent>,
+    mut game_rules_query: Query<(&game_rules::GameRules, &mut game_rules::LevelProgress)>,
+    config: Res<Configuration>,
+) {
+    let mut game_rules = game_rules_query.get_single_mut().ok();
+    for (entity, mut barker) in query.iter_mut() {
+        barker.barked = false;
+        barker.cooldown_left = f32::max(barker.cooldown_left - TIME_STEP, 0.0);
+        if !barker.requested { continue; }
+        barker.requested = false;
+        if barker.cooldown_left > 0.0 { continue; }
+        if let Some((game_rules, progress)) = game_rules.as_mut() {
+            if let Some(bark_budget) = game_rules.bark_budget {
+                if progress.barks_used >= bark_budget { continue; }
+            }
+            progress.barks_used += 1;
+        }
+        barker.barked = true;
+        barker.cooldown_left = config.bark.cooldown;
+        bark_events.send(BarkEvent { barker: entity });
     }
 }
@@ -1,7 +1,22 @@
+use bevy::utils::HashMap;
+use bevy::ecs::system::EntityCommands;
 use crate::imports::*;
 
 const NAME: &str = "Sheep";
 const Z_INDEX: f32 = 40.0;
+pub const CONFIG_SET: &str = "sheep";
+
+pub const ALIGNMENT_INFLUENCE: InfluenceKey = InfluenceKey("alignment");
+pub const COHESION_INFLUENCE: InfluenceKey = InfluenceKey("cohesion");
+pub const SEPARATION_INFLUENCE: InfluenceKey = InfluenceKey("separation");
+pub const AVOIDANCE_INFLUENCE: InfluenceKey = InfluenceKey("avoidance");
+pub const GRAZING_INFLUENCE: InfluenceKey = InfluenceKey("grazing");
+pub const INERTIA_INFLUENCE: InfluenceKey = InfluenceKey("inertia");
+pub const RUNNER_INFLUENCE: InfluenceKey = InfluenceKey("runner");
+pub const RUNNER_UNMODIFIED_INFLUENCE: InfluenceKey = InfluenceKey("runner_unmodified");
+pub const RUNNER_MAX_INFLUENCE: InfluenceKey = InfluenceKey("runner_max");
+/// Fear acts on velocity through the runner influence, this is only for debug lines.
+pub const FEAR_INFLUENCE: InfluenceKey = InfluenceKey("fear");
 
 #[derive(Default)]
 pub struct SheepPlugin;
@@ -18,6 +33,17 @@ impl Plugin for SheepPlugin {
                     }
                 }))
         });
+        app
+            .register_influence(ALIGNMENT_INFLUENCE, 1.0, InfluenceGroup::Normal, Color::RED)
+            .register_influence(COHESION_INFLUENCE, 1.0, InfluenceGroup::Normal, Color::GREEN)
+            .register_influence(SEPARATION_INFLUENCE, 1.0, InfluenceGroup::Normal, Color::ORANGE)
+            .register_influence(AVOIDANCE_INFLUENCE, 1.0, InfluenceGroup::Normal, Color::CYAN)
+            .register_influence(GRAZING_INFLUENCE, 1.0, InfluenceGroup::Normal, Color::LIME_GREEN)
+            .register_influence(INERTIA_INFLUENCE, 1.0, InfluenceGroup::Normal, Color::BLUE)
+            .register_influence(RUNNER_INFLUENCE, 1.0, InfluenceGroup::Runner, Color::YELLOW)
+            .register_influence(RUNNER_UNMODIFIED_INFLUENCE, 1.0, InfluenceGroup::DebugOnly, Color::GOLD)
+            .register_influence(RUNNER_MAX_INFLUENCE, 1.0, InfluenceGroup::DebugOnly, Color::OLIVE)
+            .register_influence(FEAR_INFLUENCE, 1.0, InfluenceGroup::DebugOnly, Color::PURPLE);
     }
 }
 
@@ -25,102 +51,279 @@ impl Plugin for SheepPlugin {
 pub struct EditorSheep {
     #[serde(default)]
     pub position: Vec2,
+    #[serde(default, skip_serializing_if = "TemperamentOverrides::is_empty")]
+    pub temperament: TemperamentOverrides,
+    /// Configuration set to use instead of [`CONFIG_SET`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_set: Option<String>,
 }
 
-fn populate(mut populate: YoleckPopulate<EditorSheep>, configuration: Res<Configuration>) {
+fn populate(
+    mut populate: YoleckPopulate<EditorSheep>,
+    configuration: Res<Configuration>,
+    mut rng: ResMut<SimulationRng>,
+) {
     populate.populate(|_ctx, data, mut commands| {
-        commands.insert(SheepBundle::new(&configuration.animation.sheep, data.position));
+        let config_set_id = match configuration.set_id_or(data.config_set.as_deref(), CONFIG_SET) {
+            Ok(config_set_id) => config_set_id,
+            Err(error) => {
+                error!("Sheep not spawned: {}", error);
+                return;
+            }
+        };
+        spawn_sheep(&mut commands, &configuration, config_set_id, data.position, &data.temperament, &mut rng.0);
     });
 }
 
-fn edit(mut edit: YoleckEdit<EditorSheep>, mut commands: Commands, mut writer: EventWriter<YoleckEditorEvent>, mut yoleck: ResMut<YoleckState>) {
+fn edit(
+    mut edit: YoleckEdit<EditorSheep>,
+    mut commands: Commands,
+    mut writer: EventWriter<YoleckEditorEvent>,
+    mut yoleck: ResMut<YoleckState>,
+    configuration: Res<Configuration>,
+) {
     edit.edit(|_ctx, data, ui| {
         if ui.add(egui::Button::new("Dolly!")).clicked() {
-            let value = serde_json::to_value(EditorSheep { position: data.position + Vec2::splat(20.0) }).unwrap();
+            let value = serde_json::to_value(EditorSheep {
+                position: data.position + Vec2::splat(20.0),
+                ..data.clone()
+            }).unwrap();
             create_editor_object(&mut commands, &mut writer, &mut yoleck, NAME, value);
         }
+        configuration_set_ui(ui, &mut data.config_set, &configuration, CONFIG_SET);
+        ui.collapsing("Temperament", |ui| {
+            temperament_overrides_ui(ui, &mut data.temperament);
+        });
     });
 }
 
 #[derive(Bundle)]
 pub struct SheepBundle {
     actor: Actor,
-    flocking: Flocking,
-    grazing: Grazing,
-    runner: Runner,
+    temperament: Temperament,
     name: Name,
     config_set_id: ConfigurationSetId,
-    inertia: Inertia,
-    counts_toward_goal: zone::CountsTowardGoal,
 }
 
 impl SheepBundle {
-    pub fn new(config_set: &AnimationSheet, position: Vec2) -> Self {
-        let mut actor = Actor::new(config_set, position.extend(Z_INDEX), Collider::ball(13.0));
+    pub fn new(
+        config: &Configuration,
+        config_set_id: ConfigurationSetId,
+        position: Vec2,
+        overrides: &TemperamentOverrides,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let config_set = config.get_set(&config_set_id);
+        let mut actor = Actor::new(
+            config.get_sheet(&config_set_id),
+            position.extend(Z_INDEX),
+            Collider::ball(config_set.collider_radius),
+        );
         actor.animation_bundle.animation_timer.0.set_elapsed(
-            Duration::from_secs_f32(rand::random::<f32>() * 1.0)
+            Duration::from_secs_f32(rng.gen::<f32>() * 1.0)
         );
         SheepBundle {
             actor,
-            flocking: Flocking::default(),
-            grazing: Grazing {
-                current_direction: None,
-                time_left: (rand::random::<f32>() * 5.0) + 0.0,
-            },
-            runner: Runner::default(),
+            temperament: Temperament::sample(&config.temperament, overrides, rng),
             name: Name::new(NAME),
-            config_set_id: ConfigurationSetId::Sheep,
-            inertia: Inertia::default(),
-            counts_toward_goal: zone::CountsTowardGoal,
+            config_set_id,
+        }
+    }
+}
+
+/// Inserts a sheep into `commands`, along with the behaviours its configuration set enables.
+pub fn spawn_sheep(
+    commands: &mut EntityCommands,
+    config: &Configuration,
+    config_set_id: ConfigurationSetId,
+    position: Vec2,
+    overrides: &TemperamentOverrides,
+    rng: &mut impl Rng,
+) {
+    let bundle = SheepBundle::new(config, config_set_id, position, overrides, rng);
+    config.get_set(&bundle.config_set_id).behaviours.insert_into(commands, rng);
+    commands.insert(bundle);
+}
+
+/// Per-sheep multipliers on top of the shared configuration, so that the flock is made of
+/// individuals rather than moving as one rigid blob. All traits are 1.0 for an average sheep.
+#[derive(Component, Clone, Copy)]
+pub struct Temperament {
+    /// Bold sheep let threats come closer before running.
+    pub boldness: f32,
+    /// Scales alignment and cohesion.
+    pub sociability: f32,
+    pub speed: f32,
+    /// Scales how far the sheep wanders while grazing.
+    pub wanderlust: f32,
+}
+
+impl Default for Temperament {
+    fn default() -> Self {
+        Temperament {
+            boldness: 1.0,
+            sociability: 1.0,
+            speed: 1.0,
+            wanderlust: 1.0,
+        }
+    }
+}
+
+impl Temperament {
+    pub fn sample(config: &TemperamentConfiguration, overrides: &TemperamentOverrides, rng: &mut impl Rng) -> Self {
+        // Always sample every trait so overriding one does not shift the random sequence
+        let sampled = Temperament {
+            boldness: config.boldness.sample(rng),
+            sociability: config.sociability.sample(rng),
+            speed: config.speed.sample(rng),
+            wanderlust: config.wanderlust.sample(rng),
+        };
+        if !config.enabled && overrides.is_empty() {
+            return Temperament::default();
+        }
+        let base = if config.enabled { sampled } else { Temperament::default() };
+        Temperament {
+            boldness: overrides.boldness.unwrap_or(base.boldness),
+            sociability: overrides.sociability.unwrap_or(base.sociability),
+            speed: overrides.speed.unwrap_or(base.speed),
+            wanderlust: overrides.wanderlust.unwrap_or(base.wanderlust),
+        }
+    }
+}
+
+/// Traits fixed in the level file instead of being sampled.
+#[derive(Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TemperamentOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boldness: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sociability: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wanderlust: Option<f32>,
+}
+
+impl TemperamentOverrides {
+    pub fn is_empty(&self) -> bool {
+        *self == TemperamentOverrides::default()
+    }
+
+    /// Fills every trait that is not set here from `fallback`.
+    pub fn or(&self, fallback: &TemperamentOverrides) -> TemperamentOverrides {
+        TemperamentOverrides {
+            boldness: self.boldness.or(fallback.boldness),
+            sociability: self.sociability.or(fallback.sociability),
+            speed: self.speed.or(fallback.speed),
+            wanderlust: self.wanderlust.or(fallback.wanderlust),
         }
     }
 }
 
+pub fn temperament_overrides_ui(ui: &mut egui::Ui, overrides: &mut TemperamentOverrides) {
+    for (label, value) in [
+        ("Boldness: ", &mut overrides.boldness),
+        ("Sociability: ", &mut overrides.sociability),
+        ("Speed: ", &mut overrides.speed),
+        ("Wanderlust: ", &mut overrides.wanderlust),
+    ] {
+        ui.horizontal(|ui| {
+            let mut is_overridden = value.is_some();
+            ui.checkbox(&mut is_overridden, label);
+            match (is_overridden, value.as_mut()) {
+                (true, Some(value)) => {
+                    ui.add(egui::DragValue::new(value).speed(0.01).clamp_range(0.0..=5.0));
+                }
+                (true, None) => *value = Some(1.0),
+                (false, _) => *value = None,
+            }
+        });
+    }
+}
+
 #[derive(Component, Default)]
 pub struct Runner {
     pub direction: Vec3,
     pub magnitude: f32,
 }
 
+/// Runners flee from every dog and wolf within its scare distance, weighted so that closer threats
+/// count for more. A bark startles every runner within the much larger bark radius at once.
 pub fn run_from_players(
-    player_query: Query<&GlobalTransform, With<player::PlayerInput>>,
-    mut runner_query: Query<(&mut Runner, &mut Influences, &GlobalTransform), Without<player::PlayerInput>>,
+    threat_query: Query<
+        (&GlobalTransform, Option<&wolf::Wolf>, Option<&player::Barker>, Option<&Velocity>, &ConfigurationSetId),
+        Or<(With<player::PlayerInput>, With<wolf::Wolf>)>,
+    >,
+    mut runner_query: Query<
+        (&mut Runner, &mut Influences, &GlobalTransform, Option<&Fear>, Option<&Temperament>, Option<&needs::Needs>),
+        Without<player::PlayerInput>,
+    >,
     config: Res<Configuration>,
-    time: Res<Time>,
 ) {
-    for player_transform in player_query.iter() {
-        let player_position = player_transform.translation();
-        for (
-            mut runner,
-            mut influences,
-            runner_transform,
-        ) in runner_query.iter_mut() {
-            let runner_position = runner_transform.translation();
-            let mut runner: &mut Runner = &mut runner;
-
-            let scare_distance = config.runner.scare_distance;
-            if runner_position.distance(player_position) < scare_distance {
-                runner.direction = (runner_position - player_position).normalize_or_zero();
-                // runner.direction = (*player_position - *runner_position).normalize_or_zero();
-                if runner.magnitude < 0.5 {
-                    runner.magnitude = 0.5
-                } else {
-                    runner.magnitude = f32::min(runner.magnitude + time.delta().as_secs_f32() * 1.0, 1.0);
-                }
+    for (
+        mut runner,
+        mut influences,
+        runner_transform,
+        fear,
+        temperament,
+        needs,
+    ) in runner_query.iter_mut() {
+        let boldness = temperament.map_or(1.0, |temperament| temperament.boldness).max(0.1);
+        let runner_position = runner_transform.translation();
+        let mut runner: &mut Runner = &mut runner;
+
+        let mut threat_direction: Option<Vec3> = None;
+        let mut bark_direction: Option<Vec3> = None;
+        for (threat_transform, wolf, barker, threat_velocity, threat_config_id) in threat_query.iter() {
+            let threat_position = threat_transform.translation();
+            let away = (runner_position - threat_position).normalize_or_zero();
+            let distance = runner_position.distance(threat_position);
+            if barker.map_or(false, |barker| barker.barked) && distance < config.bark.radius {
+                let weight = 1.0 - distance / config.bark.radius;
+                *bark_direction.get_or_insert(Vec3::ZERO) += away * weight;
+            }
+            let scare_distance = if wolf.is_some() {
+                config.hunting.scare_distance
             } else {
-                runner.magnitude -= time.delta().as_secs_f32() * 1.0;
+                let speed = threat_velocity.map_or(0.0, |velocity| velocity.linvel.length());
+                let speed_fraction = speed / config.get_set(threat_config_id).max_speed;
+                config.runner.scare_distance * config.runner.approach_scale(speed_fraction)
+            } / boldness;
+            if distance < scare_distance {
+                let weight = 1.0 - distance / scare_distance;
+                *threat_direction.get_or_insert(Vec3::ZERO) += away * weight;
             }
-            if runner.magnitude >= f32::EPSILON {
-                let mut influence = runner.direction * runner.magnitude * config.runner.scale / 10.0;
-                influences.runner_influence_unmodified = Some(influence);
-                let influence_length = influence.length();
-                if influence_length > config.runner.speed_fraction {
-                    influence *= config.runner.speed_fraction / influence_length;
-                }
-                let influence_max = influence * config.runner.speed_fraction / influence_length;
-                influences.runner_influence_max = Some(influence_max);
-                influences.runner_influence = Some(influence);
+        }
+        if let Some(threat_direction) = threat_direction {
+            runner.direction = threat_direction.normalize_or_zero();
+            if runner.magnitude < 0.5 {
+                runner.magnitude = 0.5
+            } else {
+                runner.magnitude = f32::min(runner.magnitude + TIME_STEP * 1.0, 1.0);
             }
+        } else {
+            runner.magnitude -= TIME_STEP * 1.0;
+        }
+        if let Some(bark_direction) = bark_direction {
+            runner.direction = bark_direction.normalize_or_zero();
+            runner.magnitude = f32::max(runner.magnitude, config.bark.magnitude);
+        }
+        let (direction, magnitude) = match fear {
+            Some(fear) if fear.level > runner.magnitude => (fear.direction, fear.level),
+            _ => (runner.direction, runner.magnitude),
+        };
+        // Hungry animals are reluctant to leave their food
+        let magnitude = magnitude * needs.map_or(1.0, |needs| needs.flee_factor(&config.needs));
+        if magnitude >= f32::EPSILON {
+            let mut influence = direction * magnitude * config.runner.scale / 10.0;
+            influences.set(RUNNER_UNMODIFIED_INFLUENCE, influence);
+            let influence_length = influence.length();
+            if influence_length > config.runner.speed_fraction {
+                influence *= config.runner.speed_fraction / influence_length;
+            }
+            let influence_max = influence * config.runner.speed_fraction / influence_length;
+            influences.set(RUNNER_MAX_INFLUENCE, influence_max);
+            influences.set(RUNNER_INFLUENCE, influence);
         }
     }
 }
@@ -130,47 +333,150 @@ pub struct Flocking {
     pub alignment_values: Vec<NeighbourPair>,
     pub cohesion_positions: Vec<Vec3>,
     pub separation_positions: Vec<Vec3>,
+    /// Neighbours close enough to pass their fear on, see [`spread_panic`].
+    pub fear_neighbours: Vec<Entity>,
 }
 
 pub struct NeighbourPair {
-    position: Vec3,
-    velocity: Vec3,
+    pub position: Vec3,
+    pub velocity: Vec3,
+}
+
+impl Flocking {
+    /// Refills the neighbour lists from the spatial index. `scratch` is reused between calls
+    /// to avoid allocating a candidate list for every entity.
+    pub fn collect_neighbours(
+        &mut self,
+        entity: Entity,
+        position: Vec3,
+        index: &SpatialIndex,
+        config: &FlockingConfiguration,
+        scratch: &mut Vec<usize>,
+    ) {
+        self.alignment_values.clear();
+        self.cohesion_positions.clear();
+        self.separation_positions.clear();
+        index.query_radius(position, config.max_neighbour_distance(), scratch);
+        for &neighbour_index in scratch.iter() {
+            let other = &index.entries()[neighbour_index];
+            if entity == other.entity { continue; }
+            let distance = position.distance(other.position);
+            if distance < config.alignment_distance && config.alignment_enabled {
+                self.alignment_values.push(NeighbourPair {
+                    position: other.position,
+                    velocity: other.velocity,
+                });
+            }
+            if distance < config.cohesion_distance && config.cohesion_enabled {
+                self.cohesion_positions.push(other.position);
+            }
+            if distance < config.separation_distance && config.separation_enabled {
+                self.separation_positions.push(other.position);
+            }
+        }
+    }
+
+    pub fn collect_fear_neighbours(
+        &mut self,
+        entity: Entity,
+        position: Vec3,
+        index: &SpatialIndex,
+        radius: f32,
+        scratch: &mut Vec<usize>,
+    ) {
+        self.fear_neighbours.clear();
+        index.query_radius(position, radius, scratch);
+        for &neighbour_index in scratch.iter() {
+            let other = &index.entries()[neighbour_index];
+            if entity == other.entity { continue; }
+            self.fear_neighbours.push(other.entity);
+        }
+    }
 }
 
 /// Uses the velocity of last frame to find neighbour velocities for flocking behaviour
 pub fn find_flocking_neighbours(
     mut query: Query<(Entity, &GlobalTransform, &mut Flocking), With<Velocity>>,
-    other_query: Query<(Entity, &GlobalTransform, &Velocity), With<Flocking>>,
+    index: Res<SpatialIndex>,
     config: Res<Configuration>,
+    mut scratch: Local<Vec<usize>>,
 ) {
+    let panic = &config.panic;
     let config = &config.flocking;
     if config.alignment_enabled || config.cohesion_enabled || config.separation_enabled {
         for (entity, current_transform, mut current_flocking) in query.iter_mut() {
-            current_flocking.alignment_values.clear();
-            current_flocking.cohesion_positions.clear();
-            current_flocking.separation_positions.clear();
-            for (other_entity, other_transform, other_velocity) in other_query.iter() {
-                if entity == other_entity { continue; }
-                let distance = current_transform.translation().distance(other_transform.translation());
-                if distance < config.alignment_distance && config.alignment_enabled {
-                    current_flocking.alignment_values.push(NeighbourPair {
-                        position: other_transform.translation(),
-                        velocity: Vec3::from((other_velocity.linvel, 0.0)),
-                    });
-                }
-                if distance < config.cohesion_distance && config.cohesion_enabled {
-                    current_flocking.cohesion_positions.push(other_transform.translation());
-                }
-                if distance < config.separation_distance && config.separation_enabled {
-                    current_flocking.separation_positions.push(other_transform.translation());
+            current_flocking.collect_neighbours(
+                entity,
+                current_transform.translation(),
+                &index,
+                config,
+                &mut scratch,
+            );
+        }
+    }
+    for (entity, current_transform, mut current_flocking) in query.iter_mut() {
+        if panic.enabled {
+            current_flocking.collect_fear_neighbours(
+                entity,
+                current_transform.translation(),
+                &index,
+                panic.spread_radius,
+                &mut scratch,
+            );
+        } else {
+            current_flocking.fear_neighbours.clear();
+        }
+    }
+}
+
+/// How frightened a sheep is, from 0 to 1. Sheep that are chased directly become afraid,
+/// and fear then spreads to their neighbours so that alarm travels through the flock.
+#[derive(Component, Default)]
+pub struct Fear {
+    pub level: f32,
+    pub direction: Vec3,
+}
+
+pub fn spread_panic(
+    mut query: Query<(Entity, &mut Fear, &Flocking, &Runner, &mut Influences)>,
+    config: Res<Configuration>,
+    mut previous: Local<HashMap<Entity, (f32, Vec3)>>,
+) {
+    let config = &config.panic;
+    // Fear spreads from last tick's levels so the result does not depend on iteration order
+    previous.clear();
+    previous.extend(query.iter().map(|(entity, fear, ..)| (entity, (fear.level, fear.direction))));
+    for (_entity, mut fear, flocking, runner, mut influences) in query.iter_mut() {
+        let mut level = fear.level - config.decay_rate * TIME_STEP;
+        let mut direction = fear.direction;
+        if config.enabled {
+            let mut neighbour_level: f32 = 0.0;
+            let mut neighbour_direction = Vec3::ZERO;
+            for neighbour in flocking.fear_neighbours.iter() {
+                if let Some((other_level, other_direction)) = previous.get(neighbour) {
+                    neighbour_level = neighbour_level.max(*other_level);
+                    neighbour_direction += *other_direction * *other_level;
                 }
             }
+            if neighbour_level > level {
+                level += (neighbour_level - level) * f32::min(config.spread_rate * TIME_STEP, 1.0);
+                direction = neighbour_direction.normalize_or_zero();
+            }
+        }
+        if runner.magnitude > level {
+            level = runner.magnitude;
+            direction = runner.direction;
+        }
+        fear.level = level.clamp(0.0, 1.0);
+        fear.direction = direction;
+        if fear.level >= f32::EPSILON {
+            influences.set(FEAR_INFLUENCE, fear.direction * fear.level);
         }
     }
 }
 
 pub fn calculate_flocking(
-    mut query: Query<(&mut Influences, &Flocking, &GlobalTransform, &Velocity)>,
+    mut query: Query<(&mut Influences, &Flocking, &GlobalTransform, &Velocity, &ConfigurationSetId, Option<&Temperament>)>,
     config: Res<Configuration>,
 ) {
     for (
@@ -178,7 +484,10 @@ pub fn calculate_flocking(
         flocking,
         transform,
         velocity,
+        config_id,
+        temperament,
     ) in query.iter_mut() {
+        let sociability = temperament.map_or(1.0, |temperament| temperament.sociability);
         // Alignment
         if flocking.alignment_values.len() > 0 {
             let mut alignment = Vec3::ZERO;
@@ -193,8 +502,9 @@ pub fn calculate_flocking(
                 alignment += *velocity * distance_scale;
             }
             alignment /= flocking.alignment_values.len() as f32;
-            influences.alignment_influence = Some(
-                alignment * config.flocking.alignment_scale / 10000.0
+            influences.set(
+                ALIGNMENT_INFLUENCE,
+                alignment * config.flocking.alignment_scale * sociability / 10000.0,
             );
         }
 
@@ -208,12 +518,13 @@ pub fn calculate_flocking(
             let correction_to_center = cohesion_center - transform.translation();
             let velocity_scale;
             if config.flocking.cohesion_velocity_scale {
-                velocity_scale = velocity.linvel.length() / config.sheep.max_speed;
+                velocity_scale = velocity.linvel.length() / config.get_set(config_id).max_speed;
             } else {
                 velocity_scale = 1.0;
             }
-            influences.cohesion_influence = Some(
-                correction_to_center * config.flocking.cohesion_scale * velocity_scale / 1000.0
+            influences.set(
+                COHESION_INFLUENCE,
+                correction_to_center * config.flocking.cohesion_scale * velocity_scale * sociability / 1000.0,
             );
         }
 
@@ -226,43 +537,94 @@ pub fn calculate_flocking(
                 separation += -to_neighbour / distance_recip;
             }
             separation /= flocking.separation_positions.len() as f32;
-            influences.separation_influence = Some(
-                separation * config.flocking.separation_scale / 100000.0
+            influences.set(
+                SEPARATION_INFLUENCE,
+                separation * config.flocking.separation_scale / 100000.0,
             );
         }
     }
 }
 
+/// Looks ahead along the current velocity for fences and other fixed colliders and steers
+/// toward whichever side is more open, so sheep slide along walls instead of piling up against them.
+pub fn calculate_avoidance(
+    mut query: Query<(&mut Influences, &GlobalTransform, &Velocity, &Collider), With<Flocking>>,
+    food_query: Query<(), With<food::Food>>,
+    rapier_context: Res<RapierContext>,
+    config: Res<Configuration>,
+) {
+    let config = &config.flocking;
+    if !config.avoidance_enabled { return; }
+    let not_food = |entity| !food_query.contains(entity);
+    let filter = QueryFilter::only_fixed().exclude_sensors().predicate(&not_food);
+    let clearance = |position: Vec2, direction: Vec2, collider: &Collider| {
+        rapier_context
+            .cast_shape(position, 0.0, direction, collider, config.avoidance_distance, filter)
+            .map_or(config.avoidance_distance, |(_, toi)| toi.toi)
+    };
+    for (mut influences, transform, velocity, collider) in query.iter_mut() {
+        let direction = velocity.linvel.normalize_or_zero();
+        if direction == Vec2::ZERO { continue; }
+        let position = transform.translation().truncate();
+        let ahead = clearance(position, direction, collider);
+        if ahead >= config.avoidance_distance { continue; }
+
+        let left = Vec2::from_angle(std::f32::consts::FRAC_PI_4).rotate(direction);
+        let right = Vec2::from_angle(-std::f32::consts::FRAC_PI_4).rotate(direction);
+        let steer = if clearance(position, left, collider) >= clearance(position, right, collider) {
+            direction.perp()
+        } else {
+            -direction.perp()
+        };
+        let urgency = 1.0 - ahead / config.avoidance_distance;
+        influences.set(AVOIDANCE_INFLUENCE, steer.extend(0.0) * urgency * config.avoidance_scale);
+    }
+}
+
 #[derive(Component, Default)]
 pub struct Grazing {
     pub current_direction: Option<Vec3>,
     pub time_left: f32,
 }
 
+/// Grazers alternate between standing still and wandering off, preferring to wander toward
+/// grass that has not been eaten yet.
 pub fn calculate_grazing(
-    mut query: Query<(&mut Influences, &mut Grazing)>,
+    mut query: Query<(&mut Influences, &mut Grazing, &GlobalTransform, Option<&Temperament>)>,
+    grass_query: Query<&grass::Grass>,
+    grass_index: Res<GrassIndex>,
     config: Res<Configuration>,
-    time: Res<Time>,
+    mut rng: ResMut<SimulationRng>,
+    mut neighbours: Local<Vec<usize>>,
 ) {
-    for (mut influences, mut grazing) in query.iter_mut() {
-        grazing.time_left -= time.delta().as_secs_f32();
+    for (mut influences, mut grazing, transform, temperament) in query.iter_mut() {
+        let wanderlust = temperament.map_or(1.0, |temperament| temperament.wanderlust);
+        grazing.time_left -= TIME_STEP;
         if grazing.time_left <= 0.0 {
-            if rand::random::<f32>() < 0.4 {
-                let direction = Vec3::new(
-                    rand::random::<f32>() - 0.5,
-                    rand::random::<f32>() - 0.5,
-                    rand::random::<f32>() - 0.5,
+            if rng.gen::<f32>() < 0.4 {
+                let mut direction = Vec3::new(
+                    rng.gen::<f32>() - 0.5,
+                    rng.gen::<f32>() - 0.5,
+                    rng.gen::<f32>() - 0.5,
                 );
+                if config.grass.enabled {
+                    let to_grass = grass::direction_to_grass(
+                        transform.translation(), &grass_index, &grass_query, &config.grass, &mut neighbours);
+                    if let Some(to_grass) = to_grass {
+                        // Random directions are about half a unit long
+                        direction = direction * (1.0 - config.grass.seek_bias) + to_grass * 0.5 * config.grass.seek_bias;
+                    }
+                }
                 grazing.current_direction = Some(direction);
-                grazing.time_left = rand::random::<f32>() * 5.0 + 0.5;
+                grazing.time_left = rng.gen::<f32>() * 5.0 + 0.5;
             } else {
                 grazing.current_direction = None;
             }
         }
         if let Some(current_direction) = grazing.current_direction {
-            influences.grazing_influence = Some(current_direction * config.grazing_scale / 10.0);
+            influences.set(GRAZING_INFLUENCE, current_direction * config.grazing_scale * wanderlust / 10.0);
         } else {
-            influences.grazing_influence = None;
+            influences.remove(GRAZING_INFLUENCE);
         }
     }
 }
@@ -282,7 +644,7 @@ pub fn calculate_inertia(
     ) in query.iter_mut() {
         let config_set = config.get_set(config_id);
         let influence = (velocity.linvel / config_set.max_speed) * config.inertia_scale / 100.0;
-        influences.inertia_influence = Some(Vec3::from((influence, 0.0)));
+        influences.set(INERTIA_INFLUENCE, Vec3::from((influence, 0.0)));
     }
 }
 
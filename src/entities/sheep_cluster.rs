@@ -32,12 +32,19 @@ struct EditorSheepCluster {
     num_sheep: usize,
     #[serde(default)]
     sheep: Vec<sheep::EditorSheep>,
+    /// Applies to every sheep in the cluster that does not override the trait itself.
+    #[serde(default, skip_serializing_if = "sheep::TemperamentOverrides::is_empty")]
+    temperament: sheep::TemperamentOverrides,
+    /// Configuration set of every sheep in the cluster that does not pick its own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    config_set: Option<String>,
 }
 
 fn populate(
     mut populate: YoleckPopulate<EditorSheepCluster>,
     configuration: Res<Configuration>,
     state: Res<CurrentState<GameState>>,
+    mut rng: ResMut<SimulationRng>,
 ) {
     populate.populate(|_ctx, data, mut commands| {
         commands.despawn_descendants();
@@ -62,13 +69,28 @@ fn populate(
                 IsCluster,
             ));
             for sheep in data.sheep.iter() {
-                commands.spawn(sheep::SheepBundle::new(&configuration.animation.sheep, sheep.position));
+                let config_set = sheep.config_set.as_deref().or(data.config_set.as_deref());
+                let config_set_id = match configuration.set_id_or(config_set, sheep::CONFIG_SET) {
+                    Ok(config_set_id) => config_set_id,
+                    Err(error) => {
+                        error!("Sheep in cluster not spawned: {}", error);
+                        continue;
+                    }
+                };
+                sheep::spawn_sheep(
+                    &mut commands.spawn_empty(),
+                    &configuration,
+                    config_set_id,
+                    sheep.position,
+                    &sheep.temperament.or(&data.temperament),
+                    &mut rng.0,
+                );
             };
         });
     });
 }
 
-fn edit(mut edit: YoleckEdit<EditorSheepCluster>) {
+fn edit(mut edit: YoleckEdit<EditorSheepCluster>, mut rng: ResMut<SimulationRng>, configuration: Res<Configuration>) {
     edit.edit(|_ctx, data, ui| {
         ui.horizontal(|ui| {
             ui.add(egui::Label::new("Num sheep"));
@@ -82,11 +104,19 @@ fn edit(mut edit: YoleckEdit<EditorSheepCluster>) {
             data.sheep.clear();
             let distribution = Normal::new(0.0, data.std_dev_radius).unwrap();
             for _ in 0..data.num_sheep {
-                let x = distribution.sample(&mut rand::thread_rng());
-                let y = distribution.sample(&mut rand::thread_rng());
-                data.sheep.push(sheep::EditorSheep { position: Vec2::new(x, y) });
+                let x = distribution.sample(&mut rng.0);
+                let y = distribution.sample(&mut rng.0);
+                data.sheep.push(sheep::EditorSheep {
+                    position: Vec2::new(x, y),
+                    temperament: default(),
+                    config_set: None,
+                });
             }
         }
+        configuration_set_ui(ui, &mut data.config_set, &configuration, sheep::CONFIG_SET);
+        ui.collapsing("Temperament", |ui| {
+            sheep::temperament_overrides_ui(ui, &mut data.temperament);
+        });
         ui.collapsing("Individual Sheep", |ui| {
             egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
                 for sheep in data.sheep.iter_mut() {
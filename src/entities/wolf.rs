@@ -0,0 +1,175 @@
+use crate::imports::*;
+use crate::imports::game_rules::GameRulesCommand;
+
+const NAME: &str = "Wolf";
+const Z_INDEX: f32 = 45.0;
+pub const CONFIG_SET: &str = "wolf";
+
+pub const PREDATOR_INFLUENCE: InfluenceKey = InfluenceKey("predator");
+
+#[derive(Default)]
+pub struct WolfPlugin;
+
+impl Plugin for WolfPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_yoleck_handler({
+            YoleckTypeHandler::<EditorWolf>::new(NAME)
+                .populate_with(populate)
+                .edit_with(edit)
+                .with(yoleck_vpeol_position_edit_adapter(|data: &mut EditorWolf| {
+                    YoleckVpeolTransform2dProjection {
+                        translation: &mut data.position,
+                    }
+                }))
+        });
+        app.register_influence(PREDATOR_INFLUENCE, 1.0, InfluenceGroup::Normal, Color::MAROON);
+    }
+}
+
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct EditorWolf {
+    #[serde(default)]
+    position: Vec2,
+    /// Configuration set to use instead of [`CONFIG_SET`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    config_set: Option<String>,
+}
+
+fn populate(
+    mut populate: YoleckPopulate<EditorWolf>,
+    configuration: Res<Configuration>,
+    mut rng: ResMut<SimulationRng>,
+) {
+    populate.populate(|_ctx, data, mut commands| {
+        let config_set_id = match configuration.set_id_or(data.config_set.as_deref(), CONFIG_SET) {
+            Ok(config_set_id) => config_set_id,
+            Err(error) => {
+                error!("Wolf not spawned: {}", error);
+                return;
+            }
+        };
+        let bundle = WolfBundle::new(&configuration, config_set_id, data.position);
+        configuration.get_set(&bundle.config_set_id).behaviours.insert_into(&mut commands, &mut rng.0);
+        commands.insert(bundle);
+    });
+}
+
+fn edit(mut edit: YoleckEdit<EditorWolf>, configuration: Res<Configuration>) {
+    edit.edit(|_ctx, data, ui| {
+        configuration_set_ui(ui, &mut data.config_set, &configuration, CONFIG_SET);
+    });
+}
+
+#[derive(Bundle)]
+pub struct WolfBundle {
+    actor: Actor,
+    wolf: Wolf,
+    name: Name,
+    config_set_id: ConfigurationSetId,
+}
+
+impl WolfBundle {
+    pub fn new(config: &Configuration, config_set_id: ConfigurationSetId, position: Vec2) -> Self {
+        WolfBundle {
+            actor: Actor::new(
+                config.get_sheet(&config_set_id),
+                position.extend(Z_INDEX),
+                Collider::ball(config.get_set(&config_set_id).collider_radius),
+            ),
+            wolf: Wolf::default(),
+            name: Name::new(NAME),
+            config_set_id,
+        }
+    }
+}
+
+#[derive(Component, Default)]
+pub struct Wolf {
+    pub target: Option<Entity>,
+    pub flee_time_left: f32,
+}
+
+/// Wolves stalk the closest sheep that has strayed from the centre of the flock and lunge at it
+/// once close enough. A dog coming near drives the wolf off for a while.
+pub fn hunt_sheep(
+    mut commands: Commands,
+    mut wolf_query: Query<(&mut Wolf, &mut Influences, &GlobalTransform)>,
+    sheep_query: Query<(Entity, &GlobalTransform), With<sheep::Flocking>>,
+    player_query: Query<&GlobalTransform, With<player::PlayerInput>>,
+    mut event_writer: EventWriter<GameRulesCommand>,
+    config: Res<Configuration>,
+) {
+    let config = &config.hunting;
+    let mut flock_centre = Vec2::ZERO;
+    let mut num_sheep = 0;
+    for (_, sheep_transform) in sheep_query.iter() {
+        flock_centre += sheep_transform.translation().truncate();
+        num_sheep += 1;
+    }
+    if num_sheep > 0 {
+        flock_centre /= num_sheep as f32;
+    }
+
+    let mut caught = Vec::new();
+    for (mut wolf, mut influences, wolf_transform) in wolf_query.iter_mut() {
+        let wolf_position = wolf_transform.translation().truncate();
+
+        let closest_dog = player_query.iter()
+            .map(|player_transform| player_transform.translation().truncate())
+            .min_by(|a, b| a.distance(wolf_position).total_cmp(&b.distance(wolf_position)));
+        if let Some(dog_position) = closest_dog {
+            if dog_position.distance(wolf_position) < config.dog_flee_distance {
+                wolf.flee_time_left = config.flee_time;
+                wolf.target = None;
+            }
+            if wolf.flee_time_left > 0.0 {
+                wolf.flee_time_left -= TIME_STEP;
+                let away = (wolf_position - dog_position).normalize_or_zero();
+                influences.set(PREDATOR_INFLUENCE, away.extend(0.0));
+                continue;
+            }
+        }
+
+        if num_sheep == 0 { continue; }
+        if let Some(target) = wolf.target {
+            if sheep_query.get(target).is_err() || caught.contains(&target) {
+                wolf.target = None;
+            }
+        }
+        if wolf.target.is_none() {
+            wolf.target = sheep_query.iter()
+                .filter(|(entity, sheep_transform)| {
+                    !caught.contains(entity)
+                        && sheep_transform.translation().truncate().distance(flock_centre) > config.isolation_distance
+                })
+                .min_by(|(_, a), (_, b)| {
+                    let a = a.translation().truncate().distance(wolf_position);
+                    let b = b.translation().truncate().distance(wolf_position);
+                    a.total_cmp(&b)
+                })
+                .map(|(entity, _)| entity);
+        }
+
+        if let Some((target, target_transform)) = wolf.target.and_then(|target| sheep_query.get(target).ok()) {
+            let to_target = target_transform.translation().truncate() - wolf_position;
+            let distance = to_target.length();
+            if distance < config.catch_distance {
+                commands.entity(target).despawn_recursive();
+                caught.push(target);
+                event_writer.send(GameRulesCommand::SheepLost);
+                wolf.target = None;
+            } else if distance < config.attack_distance {
+                influences.set(PREDATOR_INFLUENCE, (to_target / distance).extend(0.0));
+            } else {
+                influences.set(PREDATOR_INFLUENCE, (to_target / distance * config.stalk_speed_fraction).extend(0.0));
+            }
+        } else {
+            // Nobody has strayed, so circle in closer and wait at the edge of the flock.
+            let to_centre = flock_centre - wolf_position;
+            let lurk_distance = config.isolation_distance + config.scare_distance;
+            if to_centre.length() > lurk_distance {
+                influences.set(PREDATOR_INFLUENCE, (to_centre.normalize_or_zero() * config.stalk_speed_fraction).extend(0.0));
+            }
+        }
+    }
+}
@@ -62,7 +62,7 @@ fn populate(
             },
             Collider::cuboid(data.size.x / 2.0, data.size.y / 2.0),
             Sensor,
-            GoalZone { target: data.target },
+            GoalZone { target: data.target, num_sheep: 0, size: data.size },
         )).with_children(|commands| {
             commands.spawn(
                 Text2dBundle {
@@ -106,15 +106,17 @@ fn edit(
 }
 
 #[derive(Component)]
-struct GoalZone {
-    target: usize,
+pub struct GoalZone {
+    pub target: usize,
+    pub num_sheep: usize,
+    pub size: Vec2,
 }
 
 #[derive(Component)]
 pub struct CountsTowardGoal;
 
 fn update_goal_zones(
-    zone_query: Query<(Entity, &Children, &GoalZone)>,
+    mut zone_query: Query<(Entity, &Children, &mut GoalZone)>,
     mut text_query: Query<&mut Text>,
     sheep_query: Query<Entity, (With<Collider>, With<CountsTowardGoal>)>,
     game_mode: Res<CurrentState<GameState>>,
@@ -124,7 +126,7 @@ fn update_goal_zones(
     let mut any_complete = false;
     let mut all_complete = true;
     let mut any_zones = false;
-    for (zone_entity, zone_children, goal_zone) in zone_query.iter() {
+    for (zone_entity, zone_children, mut goal_zone) in zone_query.iter_mut() {
         let target_num_sheep = goal_zone.target;
         let mut num_sheep = 0;
         for sheep_entity in sheep_query.iter() {
@@ -132,6 +134,7 @@ fn update_goal_zones(
                 num_sheep += 1;
             }
         }
+        goal_zone.num_sheep = num_sheep;
         for child in zone_children.iter() {
             if let Ok(mut text) = text_query.get_mut(*child) {
                 for mut section in text.sections.iter_mut() {
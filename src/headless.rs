@@ -0,0 +1,201 @@
+use bevy::asset::LoadState;
+use bevy::time::TimeUpdateStrategy;
+use bevy_yoleck::YoleckRawLevel;
+use crate::imports::*;
+use crate::imports::game_rules::GameRulesCommand;
+use crate::levels::LevelEvent;
+
+/// Loads a single level without a window and steps the simulation a fixed number of ticks.
+/// Time is advanced by exactly one `TIME_STEP` per update, so a run only depends on the seed.
+pub struct HeadlessRun {
+    /// Path to a `.yol` file, relative to the assets folder.
+    pub level: String,
+    pub ticks: u32,
+    pub seed: Option<u64>,
+    /// Configuration preset to use instead of the default one.
+    pub preset: Option<String>,
+    /// Herd with the autopilot instead of leaving the dogs idle.
+    pub autopilot: bool,
+    /// Stop as soon as the level is won instead of running all ticks.
+    pub stop_when_won: bool,
+}
+
+pub struct HeadlessReport {
+    pub level: String,
+    pub seed: u64,
+    pub ticks: u32,
+    pub zones: Vec<ZoneReport>,
+    pub check_sheep_win: Option<(bool, bool)>,
+    pub won_at_tick: Option<u32>,
+    /// When too many sheep were lost and the level asked to be restarted, which ends the run.
+    pub failed_at_tick: Option<u32>,
+    pub lost_sheep: usize,
+    /// Where every moving entity ended up, in a stable order for runs of the same level.
+    pub positions: Vec<Vec2>,
+}
+
+pub struct ZoneReport {
+    pub position: Vec2,
+    pub num_sheep: usize,
+    pub target: usize,
+}
+
+#[derive(Resource)]
+struct HeadlessLevel {
+    handle: Handle<YoleckRawLevel>,
+}
+
+#[derive(Resource, Default)]
+struct HeadlessProgress {
+    ticks: u32,
+    check_sheep_win: Option<(bool, bool)>,
+    won_at_tick: Option<u32>,
+    failed_at_tick: Option<u32>,
+    lost_sheep: usize,
+}
+
+/// Loading should never take this many updates; stop instead of spinning forever on a bad path.
+const MAX_LOADING_UPDATES: u32 = 10_000;
+
+impl HeadlessRun {
+    pub fn run(&self) -> Result<HeadlessReport, String> {
+        let mut app = App::new();
+        app
+            .add_plugins(MinimalPlugins)
+            .add_plugin(TransformPlugin::default())
+            .add_plugin(HierarchyPlugin::default())
+            .add_plugin(InputPlugin::default())
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Image>()
+            .add_asset::<TextureAtlas>()
+            .add_asset::<Font>()
+            .add_loopless_state(GameState::LoadLevels)
+            .add_plugin(bevy_yoleck::YoleckPluginForGame)
+            .add_plugin(GamePlugin { seed: self.seed, preset: self.preset.clone(), autopilot: self.autopilot })
+            .add_event::<LevelEvent>()
+            .init_resource::<HeadlessProgress>()
+            .add_system(wait_for_level.run_in_state(GameState::LoadLevels))
+            .add_system(record_progress.run_in_state(GameState::Game));
+
+        let handle: Handle<YoleckRawLevel> = app.world.resource::<AssetServer>()
+            .load(self.level.trim_start_matches("assets/"));
+        app.insert_resource(HeadlessLevel { handle });
+
+        let step = Duration::from_secs_f32(TIME_STEP);
+        let mut now = bevy::utils::Instant::now();
+        let mut loading_updates = 0;
+        loop {
+            let progress = app.world.resource::<HeadlessProgress>();
+            if progress.ticks >= self.ticks
+                || progress.failed_at_tick.is_some()
+                || (self.stop_when_won && progress.won_at_tick.is_some()) {
+                break;
+            }
+            now += step;
+            app.insert_resource(TimeUpdateStrategy::ManualInstant(now));
+            app.update();
+            if app.world.resource::<CurrentState<GameState>>().0 != GameState::Game {
+                loading_updates += 1;
+                if loading_updates > MAX_LOADING_UPDATES {
+                    return Err(format!("Level {} did not load", self.level));
+                }
+            }
+        }
+
+        let mut zones = Vec::new();
+        let mut zone_query = app.world.query::<(&GlobalTransform, &zone::GoalZone)>();
+        for (transform, goal_zone) in zone_query.iter(&app.world) {
+            zones.push(ZoneReport {
+                position: transform.translation().truncate(),
+                num_sheep: goal_zone.num_sheep,
+                target: goal_zone.target,
+            });
+        }
+        let positions = app.world.query_filtered::<&GlobalTransform, With<Velocity>>()
+            .iter(&app.world)
+            .map(|transform| transform.translation().truncate())
+            .collect();
+        let progress = app.world.resource::<HeadlessProgress>();
+        Ok(HeadlessReport {
+            level: self.level.clone(),
+            seed: app.world.resource::<SimulationSeed>().0,
+            ticks: progress.ticks,
+            zones,
+            check_sheep_win: progress.check_sheep_win,
+            won_at_tick: progress.won_at_tick,
+            failed_at_tick: progress.failed_at_tick,
+            lost_sheep: progress.lost_sheep,
+            positions,
+        })
+    }
+}
+
+impl HeadlessReport {
+    pub fn print(&self) {
+        println!("Level: {}", self.level);
+        println!("Seed: {}", self.seed);
+        println!("Ticks: {} ({:.2} s simulated)", self.ticks, self.ticks as f32 * TIME_STEP);
+        for zone in self.zones.iter() {
+            println!("Goal zone at ({:.0}, {:.0}): {}/{}", zone.position.x, zone.position.y, zone.num_sheep, zone.target);
+        }
+        match self.check_sheep_win {
+            Some((all_zones_done, any_zones_done)) => {
+                println!("Last CheckSheepWin: all_zones_done: {all_zones_done}, any_zones_done: {any_zones_done}");
+            }
+            None => println!("CheckSheepWin never fired"),
+        }
+        println!("Sheep lost: {}", self.lost_sheep);
+        match self.won_at_tick {
+            Some(tick) => println!("Won after {} ticks ({:.2} s simulated)", tick, tick as f32 * TIME_STEP),
+            None => println!("Not won"),
+        }
+        if let Some(tick) = self.failed_at_tick {
+            println!("Failed after {} ticks ({:.2} s simulated), too many sheep lost", tick, tick as f32 * TIME_STEP);
+        }
+    }
+}
+
+fn wait_for_level(
+    mut commands: Commands,
+    level: Res<HeadlessLevel>,
+    asset_server: Res<AssetServer>,
+    mut yoleck_loading_command: ResMut<bevy_yoleck::YoleckLoadingCommand>,
+) {
+    if asset_server.get_load_state(&level.handle) == LoadState::Loaded {
+        *yoleck_loading_command = bevy_yoleck::YoleckLoadingCommand::FromAsset(level.handle.clone());
+        commands.insert_resource(NextState(GameState::Game));
+    }
+}
+
+fn record_progress(
+    mut progress: ResMut<HeadlessProgress>,
+    mut game_rules_reader: EventReader<GameRulesCommand>,
+    mut level_reader: EventReader<LevelEvent>,
+) {
+    progress.ticks += 1;
+    for command in game_rules_reader.iter() {
+        match command {
+            GameRulesCommand::CheckSheepWin { all_zones_done, any_zones_done } => {
+                progress.check_sheep_win = Some((*all_zones_done, *any_zones_done));
+            }
+            GameRulesCommand::SheepLost => {
+                progress.lost_sheep += 1;
+            }
+        }
+    }
+    for event in level_reader.iter() {
+        match event {
+            LevelEvent::LoadNextLevel => {
+                if progress.won_at_tick.is_none() {
+                    progress.won_at_tick = Some(progress.ticks);
+                }
+            }
+            LevelEvent::RestartLevel => {
+                if progress.failed_at_tick.is_none() {
+                    progress.failed_at_tick = Some(progress.ticks);
+                }
+            }
+            LevelEvent::LoadLevelIndex { .. } => {}
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::imports::*;
+
+const BAR_WIDTH: f32 = 160.0;
+const BAR_HEIGHT: f32 = 12.0;
+const BAR_MARGIN: f32 = 10.0;
+
+/// On-screen information for the players. Only added to the windowed game, the headless runner
+/// has nothing to draw it with.
+#[derive(Default)]
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_system(spawn_stamina_bars.run_in_state(GameState::Game))
+            .add_system(update_stamina_bars.run_in_state(GameState::Game));
+    }
+}
+
+/// The background of the stamina bar of `player`.
+#[derive(Component)]
+struct StaminaBar {
+    player: Entity,
+}
+
+#[derive(Component)]
+struct StaminaBarFill;
+
+fn spawn_stamina_bars(
+    mut commands: Commands,
+    query: Query<(Entity, &player::PlayerInput), Added<player::Stamina>>,
+    config: Res<Configuration>,
+) {
+    for (player, input) in query.iter() {
+        let tint = config.players.get(input.slot).map_or(Color::WHITE, |bindings| bindings.tint);
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            left: Val::Px(BAR_MARGIN),
+                            top: Val::Px(BAR_MARGIN + input.slot as f32 * (BAR_HEIGHT + BAR_MARGIN)),
+                            ..default()
+                        },
+                        size: Size::new(Val::Px(BAR_WIDTH), Val::Px(BAR_HEIGHT)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+                    ..default()
+                },
+                StaminaBar { player },
+                Name::new("Stamina bar"),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                            ..default()
+                        },
+                        background_color: tint.into(),
+                        ..default()
+                    },
+                    StaminaBarFill,
+                ));
+            });
+    }
+}
+
+/// Resizes the fill of every bar to the stamina of its player, and removes the bars of players
+/// that are gone, for example after a level change.
+fn update_stamina_bars(
+    mut commands: Commands,
+    bar_query: Query<(Entity, &StaminaBar, &Children)>,
+    mut fill_query: Query<(&mut Style, &mut BackgroundColor), With<StaminaBarFill>>,
+    player_query: Query<(&player::Stamina, &player::PlayerInput)>,
+    config: Res<Configuration>,
+) {
+    for (bar_entity, bar, children) in bar_query.iter() {
+        let (stamina, input) = match player_query.get(bar.player) {
+            Ok(player) => player,
+            Err(_) => {
+                commands.entity(bar_entity).despawn_recursive();
+                continue;
+            }
+        };
+        let tint = config.players.get(input.slot).map_or(Color::WHITE, |bindings| bindings.tint);
+        for &child in children.iter() {
+            if let Ok((mut style, mut color)) = fill_query.get_mut(child) {
+                style.size.width = Val::Percent(stamina.current * 100.0);
+                // Dim the bar until there is enough stamina to start sprinting again
+                let ready = stamina.sprinting || stamina.current >= config.stamina.min_to_sprint;
+                color.0 = if ready { tint } else { tint * 0.5 };
+            }
+        }
+    }
+}
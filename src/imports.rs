@@ -5,12 +5,15 @@ pub use crate::editor::*;
 pub use crate::entities::*;
 pub use crate::motion::*;
 pub use crate::assets::*;
+pub use crate::spatial_index::*;
+pub use crate::simulation::*;
 pub use bevy::prelude::*;
 pub use bevy_rapier2d::prelude::*;
 pub use bevy_prototype_debug_lines::*;
 pub use iyes_loopless::prelude::*;
 pub use bevy_inspector_egui::prelude::*;
 pub use bevy_prototype_debug_lines::*;
+pub use rand::Rng;
 pub use rand_distr::{Uniform, Normal, Distribution};
 pub use std::time::Duration;
 pub use serde::Serialize;
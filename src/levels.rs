@@ -1,14 +1,22 @@
-use bevy::asset::LoadState;
+use bevy::asset::{FileAssetIo, LoadState};
 use crate::imports::*;
 use bevy_yoleck::{YoleckLevelIndex, YoleckRawLevel};
 
+pub const LEVEL_INDEX_PATH: &str = "levels/index.yoli";
+
 #[derive(Default)]
-pub struct LevelsPlugin;
+pub struct LevelsPlugin {
+    /// The level to start at instead of the first one in the index.
+    pub start: Option<LevelSelection>,
+}
 
 impl Plugin for LevelsPlugin {
     fn build(&self, app: &mut App) {
         app
-            .insert_resource(LevelInformation::default())
+            .insert_resource(LevelInformation {
+                start: self.start.clone(),
+                ..default()
+            })
             .add_event::<LevelEvent>()
             .add_startup_system(setup)
             .add_system(wait_for_level_index.run_in_state(GameState::LoadLevelIndex))
@@ -21,20 +29,82 @@ impl Plugin for LevelsPlugin {
 #[derive(Resource, Default)]
 pub struct LevelInformation {
     current_index: usize,
+    start: Option<LevelSelection>,
     level_index: Option<Handle<YoleckLevelIndex>>,
     levels: Vec<Handle<YoleckRawLevel>>,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum LevelSelection {
+    /// Position in the level index.
+    Index(usize),
+    /// Path to a `.yol` file, relative to the assets folder.
+    File(String),
+}
+
+impl LevelSelection {
+    /// Numbers select a level by index, anything else is taken as a file.
+    pub fn parse(value: &str) -> Self {
+        match value.parse() {
+            Ok(index) => LevelSelection::Index(index),
+            Err(_) => LevelSelection::File(String::from(value.trim_start_matches("assets/"))),
+        }
+    }
+
+    /// Finds the selected level among the asset paths of the indexed levels. A file that is not
+    /// part of the index is added after the last level.
+    pub fn resolve(&self, level_paths: &mut Vec<String>) -> Result<usize, String> {
+        match self {
+            LevelSelection::Index(index) => {
+                if *index < level_paths.len() {
+                    Ok(*index)
+                } else {
+                    Err(format!("there is no level {}, the index has {} levels", index, level_paths.len()))
+                }
+            }
+            LevelSelection::File(file) => {
+                let position = level_paths.iter().position(|path| {
+                    path == file || path.strip_prefix("levels/") == Some(file.as_str())
+                });
+                Ok(position.unwrap_or_else(|| {
+                    level_paths.push(file.clone());
+                    level_paths.len() - 1
+                }))
+            }
+        }
+    }
+}
+
+/// Asset paths of the levels in the index, read straight from disk for when there is no asset
+/// server to load it with.
+pub fn read_level_paths() -> Result<Vec<String>, String> {
+    let path = FileAssetIo::get_base_path().join("assets").join(LEVEL_INDEX_PATH);
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|error| format!("could not read {}: {}", path.display(), error))?;
+    let index: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|error| format!("invalid level index {}: {}", path.display(), error))?;
+    let entries = index.get(1).and_then(|entries| entries.as_array())
+        .ok_or_else(|| format!("invalid level index {}: missing level list", path.display()))?;
+    entries.iter()
+        .map(|entry| {
+            entry.get("filename").and_then(|filename| filename.as_str())
+                .map(|filename| format!("levels/{}", filename))
+                .ok_or_else(|| format!("invalid level index {}: entry without filename", path.display()))
+        })
+        .collect()
+}
+
 pub enum LevelEvent {
     LoadLevelIndex { index: usize },
     LoadNextLevel,
+    RestartLevel,
 }
 
 fn setup(
     asset_server: Res<AssetServer>,
     mut level_information: ResMut<LevelInformation>,
 ) {
-    level_information.level_index = Some(asset_server.load("levels/index.yoli") as Handle<YoleckLevelIndex>);
+    level_information.level_index = Some(asset_server.load(LEVEL_INDEX_PATH) as Handle<YoleckLevelIndex>);
 }
 
 fn wait_for_level_index(
@@ -50,9 +120,16 @@ fn wait_for_level_index(
             println!("{:?}", assets.get(handle));
             if let Some(level_index) = assets.get(handle) {
                 assert!(level_index.len() > 0);
+                let mut level_paths: Vec<String> = level_index.iter()
+                    .map(|entry| format!("levels/{}", entry.filename))
+                    .collect();
+                if let Some(start) = level_information.start.take() {
+                    level_information.current_index = start.resolve(&mut level_paths)
+                        .unwrap_or_else(|error| panic!("could not start at level {:?}: {}", start, error));
+                }
                 let mut levels = vec![];
-                for entry in level_index.iter() {
-                    let level = asset_server.load(format!("levels/{}", entry.filename));
+                for path in level_paths {
+                    let level = asset_server.load(path);
                     levels.push(level);
                 }
                 level_information.levels = levels;
@@ -75,7 +152,6 @@ fn wait_for_levels(
     level_information.levels.iter().all(|level| -> bool {
         asset_server.get_load_state(level) == LoadState::Loaded
     }).then(|| {
-        level_information.current_index = 0;
         let first_level = dbg!(&level_information.levels).get(level_information.current_index)
             .expect("The first level should be loaded at this point");
         *yoleck_loading_command = bevy_yoleck::YoleckLoadingCommand::FromAsset(first_level.clone());
@@ -89,8 +165,13 @@ pub fn handle_level_events(
     mut event_reader: EventReader<LevelEvent>,
     mut yoleck_loading_command: ResMut<bevy_yoleck::YoleckLoadingCommand>,
     level_entities_query: Query<Entity, With<bevy_yoleck::YoleckManaged>>,
+    mut overridden: ResMut<level_settings::OverriddenConfiguration>,
+    mut config: ResMut<Configuration>,
 ) {
     for event in event_reader.iter() {
+        // The next level has to start from the global configuration, whether it has its own
+        // settings or not
+        overridden.restore(&mut config);
         match event {
             LevelEvent::LoadLevelIndex { index } => {
                 for entity in level_entities_query.iter() {
@@ -109,6 +190,14 @@ pub fn handle_level_events(
                     *yoleck_loading_command = bevy_yoleck::YoleckLoadingCommand::FromAsset(first_level.clone());
                 }
             }
+            LevelEvent::RestartLevel => {
+                for entity in level_entities_query.iter() {
+                    commands.entity(entity).despawn_recursive();
+                }
+                if let Some(level) = level_information.levels.get(level_information.current_index) {
+                    *yoleck_loading_command = bevy_yoleck::YoleckLoadingCommand::FromAsset(level.clone());
+                }
+            }
         }
     }
 }
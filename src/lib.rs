@@ -0,0 +1,86 @@
+pub mod motion;
+pub mod animation;
+pub mod editor;
+pub mod entities;
+pub mod imports;
+pub mod configuration;
+pub mod assets;
+pub mod levels;
+pub mod spatial_index;
+pub mod simulation;
+pub mod headless;
+pub mod presets;
+pub mod cli;
+pub mod wind;
+pub mod hud;
+
+use imports::*;
+
+/// Everything needed to simulate a level, without any windowing or rendering.
+/// Shared between the game and the headless runner.
+#[derive(Default)]
+pub struct GamePlugin {
+    pub seed: Option<u64>,
+    /// Configuration preset to start with instead of the default one.
+    pub preset: Option<String>,
+    /// Let the autopilot herd with every dog from the start.
+    pub autopilot: bool,
+}
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // Physics is stepped by the motion timestep instead, see `MotionPlugin`
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(30.0).with_default_system_setup(false))
+            .add_plugin(SimulationPlugin { seed: self.seed })
+            .add_plugin(presets::PresetsPlugin { preset: self.preset.clone() })
+            .add_plugin(animation::AnimationPlugin::default()) // Needs to be before anything that spawns entities
+            .add_plugin(MotionPlugin::default())
+            .add_plugin(player::PlayerPlugin::default())
+            .add_plugin(sheep::SheepPlugin::default())
+            .add_plugin(sheep_cluster::SheepClusterPlugin::default())
+            .add_plugin(food::FoodPlugin::default())
+            .add_plugin(fence::FencePlugin::default())
+            .add_plugin(grass::GrassPlugin::default())
+            .add_plugin(text::TextPlugin::default())
+            .add_plugin(zone::ZonePlugin::default())
+            .add_plugin(wolf::WolfPlugin::default())
+            .add_plugin(game_rules::GameRulesPlugin::default())
+            .add_plugin(level_settings::LevelSettingsPlugin::default())
+            .add_plugin(wind::WindPlugin::default())
+            .add_plugin(autopilot::AutopilotPlugin { enabled: self.autopilot })
+
+            .register_type::<Configuration>()
+            .insert_resource::<Configuration>(match &self.preset {
+                Some(preset) => Configuration::from_preset(preset),
+                None => Configuration::new(),
+            })
+            .insert_resource(RapierConfiguration {
+                gravity: Vec2::ZERO,
+                timestep_mode: TimestepMode::Fixed { dt: TIME_STEP, substeps: 1 },
+                ..default()
+            });
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameState {
+    LoadLevelIndex,
+    LoadLevels,
+    Game,
+    Editor,
+}
+
+
+pub fn spawn_camera(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+}
+
+pub fn update_zoom(
+    mut query: Query<&mut OrthographicProjection>,
+    config: Res<Configuration>,
+) {
+    for mut projection in query.iter_mut() {
+        projection.scale = config.zoom;
+    }
+}
@@ -1,32 +1,47 @@
-mod motion;
-mod animation;
-mod editor;
-mod entities;
-mod imports;
-mod configuration;
-mod assets;
-mod levels;
-
 use bevy_inspector_egui::quick as inspector_egui;
-use imports::*;
-
-// const TIME_STEP: f32 = 1.0 / 60.0;
+use sylt::cli::{self, CliArgs};
+use sylt::headless::HeadlessRun;
+use sylt::imports::*;
+use sylt::levels::LevelSelection;
 
 fn main() {
-    let is_editor = std::env::args().any(|arg| arg == "--editor");
+    let args = CliArgs::from_env().unwrap_or_else(|error| exit_with_error(&error));
+    if args.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+    if let Some(preset) = &args.config {
+        if !presets::preset_exists(preset) {
+            exit_with_error(&format!("there is no configuration preset {}", presets::preset_asset_path(preset)));
+        }
+    }
+    if args.headless {
+        run_headless(&args);
+        return;
+    }
+    if args.verify_levels {
+        verify_levels(&args);
+        return;
+    }
+    let window_size = args.window_size.unwrap_or(Vec2::new(1600.0, 1000.0));
     let mut app = App::new();
     app.add_plugins(DefaultPlugins
         .set(ImagePlugin::default_nearest())
+        .set(AssetPlugin {
+            // Lets configuration presets be tuned while the game is running
+            watch_for_changes: true,
+            ..default()
+        })
         .set(WindowPlugin {
             window: WindowDescriptor {
                 title: String::from("Sylt"),
-                width: 1600.0,
-                height: 1000.0,
+                width: window_size.x,
+                height: window_size.y,
                 ..default()
             },
             ..default()
         }));
-    if is_editor {
+    if args.editor {
         app
             .add_loopless_state(GameState::Editor)
             .add_plugin(EditorPlugin::default());
@@ -34,10 +49,9 @@ fn main() {
         app
             .add_loopless_state(GameState::LoadLevelIndex)
             .add_plugin(bevy_yoleck::YoleckPluginForGame)
-            .add_system(game_rules::handle_game_rules.run_in_state(GameState::Game));
+            .add_plugin(hud::HudPlugin::default());
     }
     app
-        .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(30.0))
         .add_plugin(bevy_yoleck::bevy_egui::EguiPlugin)
         .add_plugin(DebugLinesPlugin::default())
         .add_plugin(inspector_egui::ResourceInspectorPlugin::<Configuration>::default())
@@ -46,48 +60,90 @@ fn main() {
         // .add_plugin(LogDiagnosticsPlugin::default())
         // .add_plugin(FrameTimeDiagnosticsPlugin::default())
         // .add_plugin(assets::GameAssetPlugin::default())
-        .add_plugin(animation::AnimationPlugin::default()) // Needs to be before anything that spawns entities
-        .add_plugin(MotionPlugin::default())
-        .add_plugin(player::PlayerPlugin::default())
-        .add_plugin(sheep::SheepPlugin::default())
-        .add_plugin(sheep_cluster::SheepClusterPlugin::default())
-        .add_plugin(food::FoodPlugin::default())
-        .add_plugin(fence::FencePlugin::default())
-        .add_plugin(grass::GrassPlugin::default())
-        .add_plugin(text::TextPlugin::default())
-        .add_plugin(zone::ZonePlugin::default())
-        .add_plugin(levels::LevelsPlugin::default())
-        .add_plugin(game_rules::GameRulesPlugin::default())
-
-        .register_type::<Configuration>()
-        .insert_resource::<Configuration>(Configuration::new())
+        .add_plugin(GamePlugin { seed: args.seed, preset: args.config.clone(), autopilot: args.autopilot })
+        .add_plugin(levels::LevelsPlugin { start: args.level.clone() })
 
         .insert_resource(ClearColor(Color::rgb_u8(46 as u8, 34 as u8, 47 as u8)))
-        .insert_resource(RapierConfiguration { gravity: Vec2::ZERO, ..default() })
+        .add_system(draw_debug_lines.run_in_state(GameState::Game))
+        .add_system(autopilot::draw_autopilot_targets.run_in_state(GameState::Game))
+        .add_system(presets::presets_window)
         .add_system(update_zoom)
         .add_startup_system(spawn_camera);
+    if args.debug_lines {
+        app.world.resource_mut::<Configuration>().debug_lines.enable = true;
+    }
 
     app.run();
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-enum GameState {
-    LoadLevelIndex,
-    LoadLevels,
-    Game,
-    Editor,
+fn run_headless(args: &CliArgs) {
+    let selection = args.level.clone().unwrap_or(LevelSelection::Index(0));
+    let level = levels::read_level_paths()
+        .and_then(|mut level_paths| {
+            let index = selection.resolve(&mut level_paths)?;
+            Ok(level_paths.swap_remove(index))
+        })
+        .unwrap_or_else(|error| exit_with_error(&error));
+    let run = HeadlessRun {
+        level,
+        ticks: args.ticks.unwrap_or(cli::DEFAULT_HEADLESS_TICKS),
+        seed: args.seed,
+        preset: args.config.clone(),
+        autopilot: args.autopilot,
+        stop_when_won: false,
+    };
+    match run.run() {
+        Ok(report) => report.print(),
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    }
 }
 
-
-pub fn spawn_camera(mut commands: Commands) {
-    commands.spawn(Camera2dBundle::default());
+/// Lets the autopilot play every level in the index and exits with an error if any is not won.
+fn verify_levels(args: &CliArgs) {
+    let level_paths = levels::read_level_paths().unwrap_or_else(|error| exit_with_error(&error));
+    let ticks = args.ticks.unwrap_or(cli::DEFAULT_VERIFY_TICKS);
+    let mut failed = 0;
+    for level in level_paths {
+        let run = HeadlessRun {
+            level: level.clone(),
+            ticks,
+            seed: Some(args.seed.unwrap_or(cli::DEFAULT_VERIFY_SEED)),
+            preset: args.config.clone(),
+            autopilot: true,
+            stop_when_won: true,
+        };
+        match run.run() {
+            Ok(report) => match (report.won_at_tick, report.failed_at_tick) {
+                (_, Some(tick)) => {
+                    failed += 1;
+                    println!("FAIL {} (lost too many sheep after {:.2} s, seed {})", level, tick as f32 * TIME_STEP, report.seed);
+                }
+                (Some(tick), None) => println!("PASS {} (won after {:.2} s, seed {})", level, tick as f32 * TIME_STEP, report.seed),
+                (None, None) => {
+                    failed += 1;
+                    let zones: Vec<String> = report.zones.iter()
+                        .map(|zone| format!("{}/{}", zone.num_sheep, zone.target))
+                        .collect();
+                    println!("FAIL {} (not won in {} ticks, zones {}, seed {})", level, ticks, zones.join(" "), report.seed);
+                }
+            },
+            Err(error) => {
+                failed += 1;
+                println!("FAIL {} ({})", level, error);
+            }
+        }
+    }
+    if failed > 0 {
+        eprintln!("{} level(s) could not be solved", failed);
+        std::process::exit(1);
+    }
 }
 
-fn update_zoom(
-    mut query: Query<&mut OrthographicProjection>,
-    config: Res<Configuration>,
-) {
-    for mut projection in query.iter_mut() {
-        projection.scale = config.zoom;
-    }
+fn exit_with_error(error: &str) -> ! {
+    eprintln!("{}", error);
+    eprintln!("{}", cli::USAGE);
+    std::process::exit(1);
 }
@@ -3,62 +3,226 @@ use crate::imports::*;
 #[derive(Default)]
 pub struct MotionPlugin;
 
+/// Length of one simulation tick. Motion and physics run on this fixed timestep so that a run can
+/// be replayed independently of the frame rate.
+pub const TIME_STEP: f32 = 1.0 / 60.0;
+pub const MOTION_TIMESTEP: &str = "motion_timestep";
+
 impl Plugin for MotionPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(
+        app.init_resource::<SpatialIndex>();
+        app.init_resource::<GrassIndex>();
+        app.register_influence(TOTAL_INFLUENCE, 1.0, InfluenceGroup::DebugOnly, Color::GRAY);
+        app.register_influence(MAX_INFLUENCE, 1.0, InfluenceGroup::DebugOnly, Color::SILVER);
+        app.add_fixed_timestep(Duration::from_secs_f32(TIME_STEP), MOTION_TIMESTEP);
+        app.add_fixed_timestep_system_set(
+            MOTION_TIMESTEP,
+            0,
             ConditionSet::new()
                 .run_in_state(GameState::Game)
                 .label("motion_prep")
+                .label("spatial_index")
+                .with_system(update_spatial_index)
+                .with_system(update_grass_index)
+                .into()
+        );
+        app.add_fixed_timestep_system_set(
+            MOTION_TIMESTEP,
+            0,
+            ConditionSet::new()
+                .run_in_state(GameState::Game)
+                .label("motion_prep")
+                .after("spatial_index")
                 .with_system(sheep::find_flocking_neighbours)
-                // .with_run_criteria(FixedTimestep::step(TIME_STEP as f64))
                 .with_system(reset_influences)
+                .with_system(reset_speed_modifiers)
+                .with_system(player::bark)
+                .with_system(wind::update_wind)
+                .into()
+        );
+        app.add_fixed_timestep_system_set(
+            MOTION_TIMESTEP,
+            0,
+            ConditionSet::new()
+                .run_in_state(GameState::Game)
+                .label("panic")
+                .after("motion_prep")
+                .with_system(sheep::spread_panic)
                 .into()
         );
-        app.add_system_set(
+        app.add_fixed_timestep_system_set(
+            MOTION_TIMESTEP,
+            0,
             ConditionSet::new()
                 .run_in_state(GameState::Game)
                 .label("motion")
                 .after("motion_prep")
-                // .with_run_criteria(FixedTimestep::step(TIME_STEP as f64))
+                .after("panic")
                 .with_system(player::apply_player_input)
+                .with_system(autopilot::steer_dogs)
                 .with_system(sheep::run_from_players)
                 .with_system(sheep::calculate_flocking)
                 .with_system(sheep::calculate_grazing)
+                .with_system(sheep::calculate_avoidance)
                 .with_system(sheep::calculate_inertia)
+                .with_system(food::go_for_food)
+                .with_system(wolf::hunt_sheep)
+                .with_system(wind::blow_on_sheep)
+                .into()
+        );
+        app.add_fixed_timestep_system_set(
+            MOTION_TIMESTEP,
+            0,
+            ConditionSet::new()
+                .run_in_state(GameState::Game)
+                .label("speed_modifiers")
+                .after("motion")
+                .with_system(needs::update_needs)
+                .with_system(player::update_stamina)
+                .into()
+        );
+        app.add_fixed_timestep_system_set(
+            MOTION_TIMESTEP,
+            0,
+            ConditionSet::new()
+                .run_in_state(GameState::Game)
+                .label("motion_apply")
+                .after("motion")
+                .after("speed_modifiers")
+                .with_system(calculate_velocity)
+                .with_system(grass::graze_grass)
                 .into()
         );
-        app
-            .add_system_set(
-                ConditionSet::new()
-                    .run_in_state(GameState::Game)
-                    .label("motion_apply")
-                    .after("motion")
-                    .with_system(calculate_velocity)
-                    .with_system(draw_debug_lines)
-                    .into()
-            );
+        // Physics takes exactly one step per motion tick, in its own substages so the commands of
+        // each physics stage are applied before the next one. Transforms are propagated right
+        // after, so a frame that runs several ticks sees the same positions as one tick per frame.
+        let physics_stages = [PhysicsStages::SyncBackend, PhysicsStages::StepSimulation, PhysicsStages::Writeback];
+        for (index, stage) in physics_stages.into_iter().enumerate() {
+            app.add_fixed_timestep_child_stage(MOTION_TIMESTEP);
+            app.add_fixed_timestep_system_set(MOTION_TIMESTEP, index + 1, RapierPhysicsPlugin::<NoUserData>::get_systems(stage));
+        }
+        app.add_fixed_timestep_child_stage(MOTION_TIMESTEP);
+        app.add_fixed_timestep_system(MOTION_TIMESTEP, physics_stages.len() + 1, bevy::transform::systems::transform_propagate_system);
+        // Removals have to be seen in the frame they happen in, so this stays in the normal schedule
+        app.add_stage_before(
+            CoreStage::Last,
+            PhysicsStages::DetectDespawn,
+            SystemStage::parallel().with_system_set(RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsStages::DetectDespawn)),
+        );
+    }
+}
+
+
+/// Names one contribution to an entity's velocity. Each plugin declares the keys of the
+/// behaviours it calculates and registers them with [`InfluenceAppExt::register_influence`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct InfluenceKey(pub &'static str);
+
+pub const TOTAL_INFLUENCE: InfluenceKey = InfluenceKey("total");
+pub const MAX_INFLUENCE: InfluenceKey = InfluenceKey("max");
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InfluenceGroup {
+    /// Summed, then normalised to at most 1.0.
+    Normal,
+    /// Added after normalising, so it can push an entity above its max speed,
+    /// up to `RunnerConfiguration::speed_fraction`.
+    Runner,
+    /// Recorded for debug lines only, never affects velocity.
+    DebugOnly,
+}
+
+#[derive(Clone)]
+pub struct InfluenceDefinition {
+    pub key: InfluenceKey,
+    pub weight: f32,
+    pub group: InfluenceGroup,
+    pub debug_color: Color,
+}
+
+/// Every influence known to the game, in the order they are blended.
+#[derive(Resource, Default)]
+pub struct InfluenceRegistry {
+    definitions: Vec<InfluenceDefinition>,
+}
+
+impl InfluenceRegistry {
+    pub fn iter(&self) -> impl Iterator<Item=&InfluenceDefinition> {
+        self.definitions.iter()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&InfluenceDefinition> {
+        self.definitions.iter().find(|definition| definition.key.0 == name)
     }
 }
 
+pub trait InfluenceAppExt {
+    fn register_influence(&mut self, key: InfluenceKey, weight: f32, group: InfluenceGroup, debug_color: Color) -> &mut Self;
+}
 
+impl InfluenceAppExt for App {
+    /// Registering a key twice replaces the earlier definition but keeps its place in the blend order.
+    fn register_influence(&mut self, key: InfluenceKey, weight: f32, group: InfluenceGroup, debug_color: Color) -> &mut Self {
+        let definition = InfluenceDefinition { key, weight, group, debug_color };
+        let mut registry = self.world.get_resource_or_insert_with(InfluenceRegistry::default);
+        match registry.definitions.iter_mut().find(|existing| existing.key == key) {
+            Some(existing) => *existing = definition,
+            None => registry.definitions.push(definition),
+        }
+        self
+    }
+}
+
+/// The influences calculated for an entity during the current tick. There are only ever a handful,
+/// so they are kept in a small list rather than a map.
 #[derive(Component, Default)]
 pub struct Influences {
-    pub player_input_influence: Option<Vec3>,
-    pub alignment_influence: Option<Vec3>,
-    pub cohesion_influence: Option<Vec3>,
-    pub separation_influence: Option<Vec3>,
-    pub runner_influence: Option<Vec3>,
-    pub runner_influence_unmodified: Option<Vec3>,
-    pub runner_influence_max: Option<Vec3>,
-    pub grazing_influence: Option<Vec3>,
-    pub inertia_influence: Option<Vec3>,
-    pub total_influence: Option<Vec3>,
-    pub max_influence: Option<Vec3>,
+    values: Vec<(InfluenceKey, Vec3)>,
+}
+
+impl Influences {
+    pub fn set(&mut self, key: InfluenceKey, value: Vec3) {
+        match self.values.iter_mut().find(|(existing, _)| *existing == key) {
+            Some((_, existing)) => *existing = value,
+            None => self.values.push((key, value)),
+        }
+    }
+
+    pub fn get(&self, key: InfluenceKey) -> Option<Vec3> {
+        self.values.iter().find(|(existing, _)| *existing == key).map(|(_, value)| *value)
+    }
+
+    pub fn remove(&mut self, key: InfluenceKey) {
+        self.values.retain(|(existing, _)| *existing != key);
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
 }
 
 pub fn reset_influences(mut query: Query<&mut Influences>) {
-    for mut influence in query.iter_mut() {
-        *influence = Influences::default();
+    for mut influences in query.iter_mut() {
+        influences.clear();
+    }
+}
+
+/// Scales the max speed of an entity for a single tick. It is reset at the start of every tick,
+/// and each system that wants to speed up or slow down an entity multiplies in its own factor.
+#[derive(Component)]
+pub struct SpeedModifier {
+    pub multiplier: f32,
+}
+
+impl Default for SpeedModifier {
+    fn default() -> Self {
+        SpeedModifier { multiplier: 1.0 }
+    }
+}
+
+pub fn reset_speed_modifiers(mut query: Query<&mut SpeedModifier>) {
+    for mut speed_modifier in query.iter_mut() {
+        speed_modifier.multiplier = 1.0;
     }
 }
 
@@ -68,89 +232,78 @@ pub fn calculate_velocity(
         &mut Velocity,
         &mut Influences,
         &ConfigurationSetId,
+        Option<&sheep::Temperament>,
+        Option<&SpeedModifier>,
     )>,
+    registry: Res<InfluenceRegistry>,
     config: Res<Configuration>,
 ) {
     for (
         mut velocity,
         mut influences,
         set_id,
+        temperament,
+        speed_modifier,
     ) in query.iter_mut() {
         let mut total_influence = Vec3::ZERO;
-
-        if let Some(influence) = influences.player_input_influence {
-            total_influence += influence;
-        }
-        if let Some(influence) = influences.alignment_influence {
-            total_influence += influence;
-        }
-        if let Some(influence) = influences.cohesion_influence {
-            total_influence += influence;
-        }
-        if let Some(influence) = influences.separation_influence {
-            total_influence += influence;
-        }
-        if let Some(influence) = influences.grazing_influence {
-            total_influence += influence;
-        }
-        if let Some(influence) = influences.inertia_influence {
-            total_influence += influence;
+        let mut runner_influence: Option<Vec3> = None;
+        for definition in registry.iter() {
+            if let Some(influence) = influences.get(definition.key) {
+                match definition.group {
+                    InfluenceGroup::Normal => total_influence += influence * definition.weight,
+                    InfluenceGroup::Runner => {
+                        *runner_influence.get_or_insert(Vec3::ZERO) += influence * definition.weight;
+                    }
+                    InfluenceGroup::DebugOnly => {}
+                }
+            }
         }
 
         let influence_length = total_influence.length();
         if influence_length > 1.0 {
             total_influence /= influence_length;
         }
-        if let Some(influence) = influences.runner_influence {
+        if let Some(influence) = runner_influence {
             total_influence += influence;
             let influence_length = total_influence.length();
             if influence_length > config.runner.speed_fraction {
                 total_influence *= config.runner.speed_fraction / influence_length;
             }
         }
-        influences.total_influence = Some(total_influence);
-        influences.max_influence = Some(total_influence.normalize_or_zero());
+        influences.set(TOTAL_INFLUENCE, total_influence);
+        influences.set(MAX_INFLUENCE, total_influence.normalize_or_zero());
         let total_influence: Vec2 = Vec2::new(total_influence.x, total_influence.y);
         let set = config.get_set(set_id);
-        velocity.linvel = total_influence * set.max_speed;
+        let speed = temperament.map_or(1.0, |temperament| temperament.speed)
+            * speed_modifier.map_or(1.0, |speed_modifier| speed_modifier.multiplier);
+        velocity.linvel = total_influence * set.max_speed * speed;
     }
 }
 
 pub fn draw_debug_lines(
     query: Query<(&GlobalTransform, &Influences)>,
     mut lines: ResMut<DebugLines>,
+    registry: Res<InfluenceRegistry>,
     configuration: Res<Configuration>,
 ) {
     let debug_lines = &configuration.debug_lines;
     if !debug_lines.enable { return; }
-    let mut color_to_type = Vec::new();
-    color_to_type.push((Color::RED, &debug_lines.red, -2.0));
-    color_to_type.push((Color::GREEN, &debug_lines.green, 0.0));
-    color_to_type.push((Color::BLUE, &debug_lines.blue, 2.0));
-    color_to_type.push((Color::GRAY, &debug_lines.gray, 4.0));
-    for (color, debug_line, offset_degree) in color_to_type {
+    for (line_index, name) in debug_lines.influences.iter().enumerate() {
+        let definition = match registry.get(name) {
+            Some(definition) => definition,
+            None => continue,
+        };
+        // Spread the lines sideways a little so overlapping influences stay visible
+        let offset_degree = line_index as f32 * 2.0 - 2.0;
         for (transform, influences) in query.iter() {
-            let influence = match debug_line {
-                DebugLineType::None => { None }
-                DebugLineType::AlignmentInfluence => { influences.alignment_influence }
-                DebugLineType::CohesionInfluence => { influences.cohesion_influence }
-                DebugLineType::SeparationInfluence => { influences.separation_influence }
-                DebugLineType::RunnerInfluence => { influences.runner_influence }
-                DebugLineType::RunnerUnmodifiedInfluence => { influences.runner_influence_unmodified }
-                DebugLineType::RunnerMaxInfluence => { influences.runner_influence_max }
-                DebugLineType::GrazingInfluence => { influences.grazing_influence }
-                DebugLineType::InertiaInfluence => { influences.inertia_influence }
-                DebugLineType::TotalInfluence => { influences.total_influence }
-                DebugLineType::MaxInfluence => { influences.max_influence }
-            };
-            if let Some(influence) = influence {
+            if let Some(influence) = influences.get(definition.key) {
                 let line_graphics_scale = 50.0;
                 let offset = influence.cross(Vec3::Z) * offset_degree;
                 lines.line_colored(
                     transform.translation() + Vec3::Z,
                     transform.translation() + influence * line_graphics_scale + Vec3::Z + offset,
                     0.0,
-                    color,
+                    definition.debug_color,
                 );
             }
         }
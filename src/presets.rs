@@ -0,0 +1,246 @@
+use std::path::PathBuf;
+use bevy::asset::{AssetLoader, BoxedFuture, FileAssetIo, LoadContext, LoadedAsset};
+use bevy::reflect::TypeUuid;
+use bevy_yoleck::bevy_egui::EguiContext;
+use crate::imports::*;
+
+/// Folder under assets that holds the configuration presets.
+pub const PRESET_FOLDER: &str = "configuration";
+/// Loaded at startup if it exists.
+pub const DEFAULT_PRESET: &str = "default";
+const PRESET_EXTENSION: &str = "config.json";
+
+/// Loads tuning presets from disk and hot reloads the active one whenever its file changes.
+#[derive(Default)]
+pub struct PresetsPlugin {
+    /// The preset to start with, [`DEFAULT_PRESET`] if not set.
+    pub preset: Option<String>,
+}
+
+impl Plugin for PresetsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_asset::<ConfigurationFile>()
+            .add_asset_loader(ConfigurationFileLoader)
+            .insert_resource(ActivePreset {
+                name: self.preset.clone().unwrap_or_else(|| String::from(DEFAULT_PRESET)),
+                handle: None,
+            })
+            .add_startup_system(watch_active_preset)
+            .add_system(reload_configuration);
+    }
+}
+
+/// The raw contents of a preset file. It is parsed into a [`Configuration`] when applied so that
+/// a half-written file only gives a warning instead of failing the asset load.
+#[derive(TypeUuid)]
+#[uuid = "6f0b1f35-8a39-4c2f-9a3c-6f0d7d3e2b41"]
+pub struct ConfigurationFile {
+    pub contents: String,
+}
+
+struct ConfigurationFileLoader;
+
+impl AssetLoader for ConfigurationFileLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let contents = String::from_utf8(bytes.to_vec())?;
+            load_context.set_default_asset(LoadedAsset::new(ConfigurationFile { contents }));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[PRESET_EXTENSION]
+    }
+}
+
+#[derive(Resource)]
+pub struct ActivePreset {
+    pub name: String,
+    pub handle: Option<Handle<ConfigurationFile>>,
+}
+
+/// Path of a preset relative to the assets folder, as used by the asset server.
+pub fn preset_asset_path(name: &str) -> String {
+    format!("{}/{}.{}", PRESET_FOLDER, name, PRESET_EXTENSION)
+}
+
+/// Accepts a preset name, with or without the file extension. Presets only live in the preset
+/// folder, so names that could point outside of it are rejected.
+pub fn parse_preset_name(value: &str) -> Result<String, String> {
+    let name = value.strip_suffix(&format!(".{}", PRESET_EXTENSION)).unwrap_or(value);
+    if name.is_empty() || name.contains("..") || name.contains(|c: char| c == '/' || c == '\\') {
+        return Err(format!("invalid preset {}, expected the name of a preset in assets/{}", value, PRESET_FOLDER));
+    }
+    Ok(String::from(name))
+}
+
+fn preset_folder() -> PathBuf {
+    FileAssetIo::get_base_path().join("assets").join(PRESET_FOLDER)
+}
+
+fn preset_file_path(name: &str) -> PathBuf {
+    preset_folder().join(format!("{}.{}", name, PRESET_EXTENSION))
+}
+
+pub fn preset_exists(name: &str) -> bool {
+    preset_file_path(name).exists()
+}
+
+/// Reads a preset straight from disk. Returns `None` if there is no such preset.
+pub fn read_preset(name: &str) -> Result<Option<Configuration>, String> {
+    let path = preset_file_path(name);
+    if !path.exists() { return Ok(None); }
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|error| format!("could not read {}: {}", path.display(), error))?;
+    parse_preset(&contents)
+        .map(Some)
+        .map_err(|error| format!("invalid configuration preset {}: {}", path.display(), error))
+}
+
+/// Fields the preset leaves out keep their built-in values, also inside the nested configurations.
+pub fn parse_preset(contents: &str) -> Result<Configuration, String> {
+    serde_json::from_str(contents).map_err(|error| error.to_string())
+}
+
+pub fn write_preset(name: &str, config: &Configuration) -> Result<(), String> {
+    let contents = serde_json::to_string_pretty(config).map_err(|error| error.to_string())?;
+    std::fs::create_dir_all(preset_folder()).map_err(|error| error.to_string())?;
+    std::fs::write(preset_file_path(name), contents).map_err(|error| error.to_string())
+}
+
+/// Names of all presets in the preset folder, sorted.
+pub fn list_presets() -> Vec<String> {
+    let suffix = format!(".{}", PRESET_EXTENSION);
+    let mut presets: Vec<String> = std::fs::read_dir(preset_folder())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| entry.file_name().to_str().map(String::from))
+                .filter_map(|file_name| file_name.strip_suffix(&suffix).map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+    presets.sort();
+    presets
+}
+
+fn watch_active_preset(mut active_preset: ResMut<ActivePreset>, asset_server: Res<AssetServer>) {
+    if preset_exists(&active_preset.name) {
+        active_preset.handle = Some(asset_server.load(preset_asset_path(&active_preset.name)));
+    }
+}
+
+fn reload_configuration(
+    mut events: EventReader<AssetEvent<ConfigurationFile>>,
+    active_preset: Res<ActivePreset>,
+    files: Res<Assets<ConfigurationFile>>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut config: ResMut<Configuration>,
+    mut overridden: ResMut<level_settings::OverriddenConfiguration>,
+) {
+    for event in events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+        if Some(handle) != active_preset.handle.as_ref() { continue; }
+        let file = match files.get(handle) {
+            Some(file) => file,
+            None => continue,
+        };
+        match parse_preset(&file.contents) {
+            Ok(loaded) => {
+                overridden.without_overrides(&mut config, |config| config.apply(loaded));
+                load_missing_sprite_sheets(&asset_server, &mut texture_atlases, &mut config);
+                info!("Applied configuration preset \"{}\"", active_preset.name);
+            }
+            Err(error) => warn!("Could not apply configuration preset \"{}\": {}", active_preset.name, error),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PresetsWindowState {
+    new_name: String,
+    presets: Option<Vec<String>>,
+    message: Option<String>,
+}
+
+/// Lets designers save the current tuning, for example after adjusting it in the inspector,
+/// and switch between saved presets.
+pub fn presets_window(
+    mut egui_context: ResMut<EguiContext>,
+    mut state: Local<PresetsWindowState>,
+    mut active_preset: ResMut<ActivePreset>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut config: ResMut<Configuration>,
+    mut overridden: ResMut<level_settings::OverriddenConfiguration>,
+) {
+    let state = &mut *state;
+    let presets = state.presets.get_or_insert_with(list_presets);
+    let mut refresh = false;
+    egui::Window::new("Configuration presets").show(egui_context.ctx_mut(), |ui| {
+        ui.label(format!("Active: {}", active_preset.name));
+        if ui.button("Save active").clicked() {
+            // Level settings only apply to their level and are left out of the preset
+            let saved = overridden.without_overrides(&mut config, |config| write_preset(&active_preset.name, config));
+            state.message = Some(match saved {
+                Ok(()) => format!("Saved \"{}\"", active_preset.name),
+                Err(error) => format!("Could not save: {}", error),
+            });
+            refresh = true;
+        }
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut state.new_name);
+            let name = state.new_name.trim();
+            if ui.add_enabled(!name.is_empty(), egui::Button::new("Save as")).clicked() {
+                let saved = parse_preset_name(name).and_then(|name| {
+                    overridden.without_overrides(&mut config, |config| write_preset(&name, config))?;
+                    Ok(name)
+                });
+                state.message = Some(match saved {
+                    Ok(name) => format!("Saved \"{}\"", name),
+                    Err(error) => format!("Could not save: {}", error),
+                });
+                refresh = true;
+            }
+        });
+        ui.separator();
+        for preset in presets.iter() {
+            ui.horizontal(|ui| {
+                ui.label(preset);
+                if ui.button("Load").clicked() {
+                    match read_preset(preset) {
+                        Ok(Some(loaded)) => {
+                            overridden.without_overrides(&mut config, |config| config.apply(loaded));
+                            load_missing_sprite_sheets(&asset_server, &mut texture_atlases, &mut config);
+                            // Watch the new preset so that edits to its file are picked up
+                            active_preset.name = preset.clone();
+                            active_preset.handle = Some(asset_server.load(preset_asset_path(preset)));
+                            state.message = Some(format!("Loaded \"{}\"", preset));
+                        }
+                        Ok(None) => state.message = Some(format!("\"{}\" no longer exists", preset)),
+                        Err(error) => {
+                            warn!("Could not load configuration preset \"{}\": {}", preset, error);
+                            state.message = Some(format!("Could not load \"{}\": {}", preset, error));
+                        }
+                    }
+                }
+            });
+        }
+        if let Some(message) = &state.message {
+            ui.label(message);
+        }
+    });
+    if refresh {
+        state.presets = None;
+    }
+}
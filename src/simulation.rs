@@ -0,0 +1,33 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use crate::imports::*;
+
+/// Owns the random number generator every gameplay decision goes through, so that the same seed
+/// and the same input replay a level identically.
+#[derive(Default)]
+pub struct SimulationPlugin {
+    /// Seed for the simulation. A random seed is picked and logged when not set.
+    pub seed: Option<u64>,
+}
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        let seed = self.seed.unwrap_or_else(rand::random);
+        info!("Simulation seed: {seed}");
+        app
+            .insert_resource(SimulationSeed(seed))
+            .insert_resource(SimulationRng::from_seed(seed));
+    }
+}
+
+#[derive(Resource)]
+pub struct SimulationSeed(pub u64);
+
+#[derive(Resource, Deref, DerefMut)]
+pub struct SimulationRng(pub StdRng);
+
+impl SimulationRng {
+    pub fn from_seed(seed: u64) -> Self {
+        SimulationRng(StdRng::seed_from_u64(seed))
+    }
+}
@@ -0,0 +1,158 @@
+use bevy::utils::HashMap;
+use crate::imports::*;
+
+/// Uniform grid over the positions and velocities of every flocking entity, rebuilt once per frame
+/// so neighbour searches only have to look at nearby cells instead of the whole flock.
+#[derive(Resource, Default)]
+pub struct SpatialIndex {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<usize>>,
+    entries: Vec<SpatialEntry>,
+}
+
+#[derive(Clone)]
+pub struct SpatialEntry {
+    pub entity: Entity,
+    pub position: Vec3,
+    pub velocity: Vec3,
+}
+
+impl SpatialIndex {
+    /// Replaces the contents of the index. Cells that are still occupied keep their allocations
+    /// between rebuilds, cells left empty are dropped so the map does not grow as flocks wander.
+    pub fn rebuild(&mut self, cell_size: f32, entries: impl Iterator<Item=SpatialEntry>) {
+        self.cell_size = f32::max(cell_size, 1.0);
+        self.entries.clear();
+        self.entries.extend(entries);
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        for (index, entry) in self.entries.iter().enumerate() {
+            let cell = Self::cell_of(self.cell_size, entry.position);
+            self.cells.entry(cell).or_default().push(index);
+        }
+        self.cells.retain(|_, cell| !cell.is_empty());
+    }
+
+    pub fn entries(&self) -> &[SpatialEntry] {
+        &self.entries
+    }
+
+    /// Fills `result` with the indices of all entries within `radius` of `position`.
+    /// Indices are sorted, so iterating them visits entries in the order they were inserted,
+    /// which keeps the neighbour lists identical to a brute force search.
+    pub fn query_radius(&self, position: Vec3, radius: f32, result: &mut Vec<usize>) {
+        result.clear();
+        if self.entries.is_empty() { return; }
+        let min = Self::cell_of(self.cell_size, position - Vec3::splat(radius));
+        let max = Self::cell_of(self.cell_size, position + Vec3::splat(radius));
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                if let Some(cell) = self.cells.get(&IVec2::new(x, y)) {
+                    for &index in cell.iter() {
+                        if self.entries[index].position.distance(position) < radius {
+                            result.push(index);
+                        }
+                    }
+                }
+            }
+        }
+        result.sort_unstable();
+    }
+
+    fn cell_of(cell_size: f32, position: Vec3) -> IVec2 {
+        IVec2::new(
+            (position.x / cell_size).floor() as i32,
+            (position.y / cell_size).floor() as i32,
+        )
+    }
+}
+
+/// The grass of the level, so grazers can find something to eat nearby.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct GrassIndex(pub SpatialIndex);
+
+/// Grass does not move, so the index is only rebuilt when grass is placed, moved or removed, or
+/// when the seek distance it is laid out by changes.
+pub fn update_grass_index(
+    query: Query<(Entity, &GlobalTransform), With<grass::Grass>>,
+    changed_query: Query<(), (With<grass::Grass>, Changed<GlobalTransform>)>,
+    mut index: ResMut<GrassIndex>,
+    config: Res<Configuration>,
+    mut cell_size: Local<Option<f32>>,
+) {
+    // Removals are found by the count rather than `RemovedComponents`, which is cleared every
+    // frame and so misses grass despawned in frames without a fixed timestep tick
+    let removed = query.iter().len() != index.entries().len();
+    let resized = *cell_size != Some(config.grass.seek_distance);
+    if !removed && !resized && changed_query.is_empty() { return; }
+    *cell_size = Some(config.grass.seek_distance);
+    index.rebuild(
+        config.grass.seek_distance,
+        query.iter().map(|(entity, transform)| SpatialEntry {
+            entity,
+            position: transform.translation(),
+            velocity: Vec3::ZERO,
+        }),
+    );
+}
+
+pub fn update_spatial_index(
+    query: Query<(Entity, &GlobalTransform, &Velocity), With<sheep::Flocking>>,
+    mut index: ResMut<SpatialIndex>,
+    config: Res<Configuration>,
+) {
+    index.rebuild(
+        config.flocking.max_neighbour_distance(),
+        query.iter().map(|(entity, transform, velocity)| SpatialEntry {
+            entity,
+            position: transform.translation(),
+            velocity: Vec3::from((velocity.linvel, 0.0)),
+        }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force(entries: &[SpatialEntry], position: Vec3, radius: f32) -> Vec<usize> {
+        (0..entries.len()).filter(|&index| entries[index].position.distance(position) < radius).collect()
+    }
+
+    #[test]
+    fn query_radius_matches_brute_force() {
+        // Points every 5 units from -30 to 30, so many lie exactly on the borders of 10 unit cells
+        let entries: Vec<SpatialEntry> = (-6..=6)
+            .flat_map(|x| (-6..=6).map(move |y| Vec3::new(x as f32 * 5.0, y as f32 * 5.0, 0.0)))
+            .enumerate()
+            .map(|(index, position)| SpatialEntry {
+                entity: Entity::from_raw(index as u32),
+                position,
+                velocity: Vec3::ZERO,
+            })
+            .collect();
+        let mut index = SpatialIndex::default();
+        index.rebuild(10.0, entries.iter().cloned());
+        let mut result = Vec::new();
+        for position in [Vec3::ZERO, Vec3::new(-10.0, -10.0, 0.0), Vec3::new(-12.5, 7.5, 0.0), Vec3::new(29.9, -0.1, 0.0)] {
+            for radius in [5.0, 10.0, 12.5, 25.0] {
+                index.query_radius(position, radius, &mut result);
+                assert_eq!(result, brute_force(&entries, position, radius), "at {position} with radius {radius}");
+            }
+        }
+    }
+
+    #[test]
+    fn query_radius_excludes_entries_at_exactly_the_radius() {
+        let entries = [Vec3::new(-10.0, 0.0, 0.0), Vec3::new(-9.5, 0.0, 0.0)]
+            .into_iter()
+            .enumerate()
+            .map(|(index, position)| SpatialEntry { entity: Entity::from_raw(index as u32), position, velocity: Vec3::ZERO });
+        let mut index = SpatialIndex::default();
+        index.rebuild(10.0, entries);
+        let mut result = Vec::new();
+        index.query_radius(Vec3::ZERO, 10.0, &mut result);
+        assert_eq!(result, vec![1]);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::imports::*;
+
+pub const WIND_INFLUENCE: InfluenceKey = InfluenceKey("wind");
+
+#[derive(Default)]
+pub struct WindPlugin;
+
+impl Plugin for WindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wind>();
+        app.register_influence(WIND_INFLUENCE, 1.0, InfluenceGroup::Normal, Color::CYAN);
+    }
+}
+
+/// A steady breeze plus a noise field of gusts that drifts across the level with the wind.
+#[derive(Resource, Default)]
+pub struct Wind {
+    /// How far the gusts have travelled along the wind direction.
+    travelled: f32,
+    /// Simulated seconds of wind so far.
+    time: f32,
+}
+
+impl Wind {
+    pub fn direction(config: &WindConfiguration) -> Vec2 {
+        let angle = config.direction.to_radians();
+        Vec2::new(angle.cos(), angle.sin())
+    }
+
+    /// Strength of the wind at `position`, 0.0 when the wind is disabled.
+    pub fn strength_at(&self, config: &WindConfiguration, position: Vec2) -> f32 {
+        if !config.enabled { return 0.0; }
+        let size = f32::max(config.gust_size, 1.0);
+        let sample = (position - Self::direction(config) * self.travelled) / size;
+        config.base_strength + config.gust_strength * value_noise(sample)
+    }
+
+    /// How far the grass at `position` leans over, between 0.0 and the wind strength. Each blade
+    /// is a little behind the one upwind of it so the sway ripples through the field.
+    pub fn sway_at(&self, config: &WindConfiguration, position: Vec2) -> f32 {
+        let size = f32::max(config.gust_size, 1.0);
+        let phase = self.time * config.sway_frequency - Self::direction(config).dot(position) / size;
+        self.strength_at(config, position) * (0.5 + 0.5 * (phase * std::f32::consts::TAU).sin())
+    }
+}
+
+pub fn update_wind(mut wind: ResMut<Wind>, config: Res<Configuration>) {
+    wind.travelled += config.wind.speed * TIME_STEP;
+    wind.time += TIME_STEP;
+}
+
+/// Pushes sheep along with the gusts that are strong enough to be felt.
+pub fn blow_on_sheep(
+    mut query: Query<(&mut Influences, &GlobalTransform), With<sheep::Flocking>>,
+    wind: Res<Wind>,
+    config: Res<Configuration>,
+) {
+    let config = &config.wind;
+    if !config.influence_enabled { return; }
+    let direction = Wind::direction(config).extend(0.0);
+    for (mut influences, transform) in query.iter_mut() {
+        let strength = wind.strength_at(config, transform.translation().truncate());
+        if strength > config.influence_threshold {
+            influences.set(WIND_INFLUENCE, direction * (strength - config.influence_threshold) * config.influence_scale);
+        }
+    }
+}
+
+/// Smoothly interpolated random values on an integer grid, between 0.0 and 1.0. The same point
+/// always gives the same value, so the wind does not depend on the simulation seed.
+fn value_noise(point: Vec2) -> f32 {
+    let cell = point.floor();
+    let fraction = point - cell;
+    let smooth = fraction * fraction * (Vec2::splat(3.0) - 2.0 * fraction);
+    let corner = |x: i32, y: i32| hash(cell.x as i32 + x, cell.y as i32 + y);
+    let bottom = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * smooth.x;
+    let top = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * smooth.x;
+    bottom + (top - bottom) * smooth.y
+}
+
+fn hash(x: i32, y: i32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x8da6_b343) ^ (y as u32).wrapping_mul(0xd816_3841);
+    h = (h ^ (h >> 13)).wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 16;
+    h as f32 / u32::MAX as f32
+}
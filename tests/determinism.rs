@@ -0,0 +1,21 @@
+use sylt::headless::HeadlessRun;
+
+fn run_level(seed: u64) -> Vec<bevy::prelude::Vec2> {
+    let run = HeadlessRun {
+        level: String::from("levels/Two clusters.yol"),
+        ticks: 600,
+        seed: Some(seed),
+        preset: None,
+        autopilot: true,
+        stop_when_won: false,
+    };
+    run.run().expect("level should load").positions
+}
+
+#[test]
+fn same_seed_gives_same_positions() {
+    let first = run_level(1);
+    let second = run_level(1);
+    assert!(!first.is_empty());
+    assert_eq!(first, second);
+}
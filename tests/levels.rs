@@ -0,0 +1,31 @@
+use sylt::cli;
+use sylt::headless::HeadlessRun;
+use sylt::levels;
+
+/// Same check as `--verify-levels`: the autopilot has to win every level in the index.
+/// Simulates every level for up to `DEFAULT_VERIFY_TICKS`, so it only runs on request with
+/// `cargo test --test levels -- --ignored`.
+#[test]
+#[ignore = "slow, simulates every level"]
+fn autopilot_wins_every_level() {
+    let level_paths = levels::read_level_paths().expect("level index should be readable");
+    assert!(!level_paths.is_empty());
+    let mut failures = Vec::new();
+    for level in level_paths {
+        let run = HeadlessRun {
+            level: level.clone(),
+            ticks: cli::DEFAULT_VERIFY_TICKS,
+            seed: Some(cli::DEFAULT_VERIFY_SEED),
+            preset: None,
+            autopilot: true,
+            stop_when_won: true,
+        };
+        match run.run() {
+            Ok(report) if report.failed_at_tick.is_some() => failures.push(format!("{}: lost too many sheep", level)),
+            Ok(report) if report.won_at_tick.is_none() => failures.push(format!("{}: not won", level)),
+            Ok(_) => {}
+            Err(error) => failures.push(format!("{}: {}", level, error)),
+        }
+    }
+    assert!(failures.is_empty(), "levels not solved with seed {}:\n{}", cli::DEFAULT_VERIFY_SEED, failures.join("\n"));
+}